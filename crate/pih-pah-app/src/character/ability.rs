@@ -0,0 +1,404 @@
+use std::fmt::Display;
+
+use crate::component::RespawnEvent;
+use crate::lobby::{ButtonEdge, Character, LobbyState, PlayerInput, PlayerViewDirection};
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::character::update_grounded;
+use super::{
+    CharacterController, Damage, KnockbackEvent, GROUND_POUND_DAMAGE, PLAYER_SIZE, SHOVE_DAMAGE,
+};
+
+pub const DASH_SPEED: f32 = 30.;
+pub const GROUND_POUND_SPEED: f32 = 40.;
+pub const GROUND_POUND_RADIUS: f32 = 8.;
+/// Impulse at the center of the ground pound, fading out to its radius
+pub const GROUND_POUND_IMPULSE: f32 = 80.;
+/// Reach in front of the character surface
+pub const GRAB_RANGE: f32 = 3.;
+pub const SHOVE_RANGE: f32 = 3.;
+/// Half angle in radians of the cone in front of the character
+pub const SHOVE_HALF_ANGLE: f32 = 0.6;
+pub const SHOVE_IMPULSE: f32 = 80.;
+/// Extra impulse per m/s the attacker moves towards the victim
+pub const SHOVE_VELOCITY_IMPULSE: f32 = 6.;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Ability {
+    Dash,
+    GroundPound,
+    Grab,
+    Shove,
+}
+
+impl Display for Ability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ability::Dash => write!(f, "Dash"),
+            Ability::GroundPound => write!(f, "Ground pound"),
+            Ability::Grab => write!(f, "Grab"),
+            Ability::Shove => write!(f, "Shove"),
+        }
+    }
+}
+
+impl Ability {
+    pub fn all() -> [Ability; 4] {
+        [
+            Ability::Dash,
+            Ability::GroundPound,
+            Ability::Grab,
+            Ability::Shove,
+        ]
+    }
+
+    /// Seconds before the ability can be used again.
+    pub fn cooldown(&self) -> f32 {
+        match self {
+            Ability::Dash => 2.,
+            Ability::GroundPound => 4.,
+            Ability::Grab => 1.,
+            Ability::Shove => 0.8,
+        }
+    }
+}
+
+/// Seconds left before each ability is ready, replicated for the HUD.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component, Serialize, Deserialize)]
+pub struct AbilityCooldowns {
+    pub dash: f32,
+    pub ground_pound: f32,
+    pub grab: f32,
+    pub shove: f32,
+}
+
+impl AbilityCooldowns {
+    pub fn remaining(&self, ability: Ability) -> f32 {
+        match ability {
+            Ability::Dash => self.dash,
+            Ability::GroundPound => self.ground_pound,
+            Ability::Grab => self.grab,
+            Ability::Shove => self.shove,
+        }
+    }
+
+    pub fn ready(&self, ability: Ability) -> bool {
+        self.remaining(ability) <= 0.
+    }
+
+    pub fn start(&mut self, ability: Ability) {
+        let remaining = match ability {
+            Ability::Dash => &mut self.dash,
+            Ability::GroundPound => &mut self.ground_pound,
+            Ability::Grab => &mut self.grab,
+            Ability::Shove => &mut self.shove,
+        };
+        *remaining = ability.cooldown();
+    }
+
+    fn tick(&mut self, dt: f32) {
+        for remaining in [
+            &mut self.dash,
+            &mut self.ground_pound,
+            &mut self.grab,
+            &mut self.shove,
+        ] {
+            *remaining = (*remaining - dt).max(0.);
+        }
+    }
+}
+
+/// Ability buttons the host turns into presses.
+#[derive(Debug, Default, Component)]
+pub struct AbilityButtons {
+    dash: ButtonEdge,
+    ground_pound: ButtonEdge,
+    shove: ButtonEdge,
+}
+
+/// Falling onto the ground after a ground pound.
+#[derive(Debug, Component)]
+pub struct GroundPounding;
+
+/// Dynamic body held by a character through `joint`, a child of the character.
+#[derive(Debug, Component)]
+pub struct Grabbing {
+    pub target: Entity,
+    joint: Entity,
+}
+
+pub struct AbilityPlugins;
+
+impl Plugin for AbilityPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                tick_cooldowns,
+                dash,
+                ground_pound,
+                ground_pound_land,
+                grab,
+                shove,
+            )
+                .chain()
+                .after(update_grounded)
+                .run_if(
+                    not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
+                ),
+        )
+        .add_systems(
+            Update,
+            release_grab.run_if(
+                not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
+            ),
+        );
+    }
+}
+
+fn tick_cooldowns(time: Res<Time>, mut query: Query<&mut AbilityCooldowns>) {
+    for mut cooldowns in query.iter_mut() {
+        if Ability::all()
+            .into_iter()
+            .any(|ability| !cooldowns.ready(ability))
+        {
+            cooldowns.tick(time.delta_seconds());
+        }
+    }
+}
+
+/// Air dash along the view direction.
+fn dash(
+    mut query: Query<(
+        &mut LinearVelocity,
+        &mut AbilityCooldowns,
+        &mut AbilityButtons,
+        &PlayerInput,
+        &CharacterController,
+    )>,
+) {
+    for (mut linear_velocity, mut cooldowns, mut buttons, input, controller) in query.iter_mut() {
+        let pressed = buttons.dash.pressed(input.dash);
+        if !pressed || controller.grounded || !cooldowns.ready(Ability::Dash) {
+            continue;
+        }
+        let direction = PlayerViewDirection::from(input)
+            .look()
+            .mul_vec3(Vec3::NEG_Z);
+        linear_velocity.0 = direction * DASH_SPEED;
+        cooldowns.start(Ability::Dash);
+    }
+}
+
+fn ground_pound(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut LinearVelocity,
+        &AbilityCooldowns,
+        &mut AbilityButtons,
+        &PlayerInput,
+        &CharacterController,
+        Has<GroundPounding>,
+    )>,
+) {
+    for (entity, mut linear_velocity, cooldowns, mut buttons, input, controller, pounding) in
+        query.iter_mut()
+    {
+        // the button is followed while pounding too, a press held through it is not a new one
+        let pressed = buttons.ground_pound.pressed(input.ground_pound);
+        if !pressed || pounding || controller.grounded || !cooldowns.ready(Ability::GroundPound) {
+            continue;
+        }
+        linear_velocity.0 = Vec3::NEG_Y * GROUND_POUND_SPEED;
+        commands.entity(entity).insert(GroundPounding);
+    }
+}
+
+/// Pushes every dynamic body around a ground pound away once it lands on the ground.
+fn ground_pound_land(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    mut knockback_event: EventWriter<KnockbackEvent>,
+    mut pounding_query: Query<
+        (
+            Entity,
+            &Character,
+            &Position,
+            &CharacterController,
+            &mut AbilityCooldowns,
+        ),
+        With<GroundPounding>,
+    >,
+    mut body_query: Query<(
+        &RigidBody,
+        &Position,
+        &InverseMass,
+        &mut LinearVelocity,
+        Option<&mut Damage>,
+    )>,
+) {
+    for (entity, character, position, controller, mut cooldowns) in pounding_query.iter_mut() {
+        // walls, bodies and sensor volumes on the way down do not end it
+        if !controller.grounded {
+            continue;
+        }
+        commands.entity(entity).remove::<GroundPounding>();
+        cooldowns.start(Ability::GroundPound);
+
+        let nearby = spatial_query.shape_intersections(
+            &Collider::ball(GROUND_POUND_RADIUS),
+            position.0,
+            Quat::IDENTITY,
+            SpatialQueryFilter::new().without_entities([entity]),
+        );
+        for other in nearby {
+            let Ok((rigid_body, other_position, inverse_mass, mut linear_velocity, damage)) =
+                body_query.get_mut(other)
+            else {
+                continue;
+            };
+            if !rigid_body.is_dynamic() {
+                continue;
+            }
+            let multiplier = match damage {
+                Some(mut damage) => {
+                    knockback_event.send(KnockbackEvent {
+                        attacker: character.id,
+                        victim: other,
+                    });
+                    damage.hit(GROUND_POUND_DAMAGE)
+                }
+                None => 1.,
+            };
+            let offset = other_position.0 - position.0;
+            let falloff = 1. - (offset.length() / GROUND_POUND_RADIUS).min(1.);
+            // lift a little so bodies resting on the ground get off it
+            let direction = (offset.normalize_or_zero() + Vec3::Y * 0.5).normalize();
+            linear_velocity.0 +=
+                direction * GROUND_POUND_IMPULSE * falloff * multiplier * inverse_mass.0;
+        }
+    }
+}
+
+/// Holds the dynamic body in front of the character with a distance joint.
+fn grab(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    query: Query<
+        (Entity, &Position, &AbilityCooldowns, &PlayerInput),
+        (With<CharacterController>, Without<Grabbing>),
+    >,
+    body_query: Query<(&RigidBody, &Position), Without<Character>>,
+) {
+    for (entity, position, cooldowns, input) in query.iter() {
+        if !input.grab || !cooldowns.ready(Ability::Grab) {
+            continue;
+        }
+        let direction = PlayerViewDirection::from(input)
+            .rotation()
+            .mul_vec3(Vec3::NEG_Z);
+        let Some(hit) = spatial_query.cast_ray(
+            position.0,
+            direction,
+            PLAYER_SIZE * 0.5 + GRAB_RANGE,
+            true,
+            SpatialQueryFilter::new().without_entities([entity]),
+        ) else {
+            continue;
+        };
+        let Ok((rigid_body, target_position)) = body_query.get(hit.entity) else {
+            continue;
+        };
+        if !rigid_body.is_dynamic() {
+            continue;
+        }
+
+        let rest_length = position.0.distance(target_position.0);
+        let joint = commands
+            .spawn(DistanceJoint::new(entity, hit.entity).with_rest_length(rest_length))
+            .id();
+        commands.entity(entity).add_child(joint).insert(Grabbing {
+            target: hit.entity,
+            joint,
+        });
+    }
+}
+
+/// Pushes other characters in a cone in front of the character, harder the faster it runs into them.
+fn shove(
+    mut knockback_event: EventWriter<KnockbackEvent>,
+    mut query: Query<(
+        Entity,
+        &Character,
+        &Position,
+        &InverseMass,
+        &mut LinearVelocity,
+        &mut AbilityCooldowns,
+        &mut Damage,
+        &mut AbilityButtons,
+        &PlayerInput,
+    )>,
+) {
+    let mut shoves = Vec::new();
+    for (entity, character, position, _, linear_velocity, mut cooldowns, _, mut buttons, input) in
+        query.iter_mut()
+    {
+        let pressed = buttons.shove.pressed(input.shove);
+        if !pressed || !cooldowns.ready(Ability::Shove) {
+            continue;
+        }
+        cooldowns.start(Ability::Shove);
+        let forward = PlayerViewDirection::from(input)
+            .rotation()
+            .mul_vec3(Vec3::NEG_Z);
+        let speed = linear_velocity.dot(forward).max(0.);
+        shoves.push((entity, character.id, position.0, forward, speed));
+    }
+
+    for (attacker_entity, attacker, origin, forward, speed) in shoves {
+        for (entity, _, position, inverse_mass, mut linear_velocity, _, mut damage, _, _) in
+            query.iter_mut()
+        {
+            if entity == attacker_entity {
+                continue;
+            }
+            let offset = position.0 - origin;
+            let flat = Vec3::new(offset.x, 0., offset.z);
+            if offset.length() > PLAYER_SIZE + SHOVE_RANGE
+                || flat.angle_between(forward) > SHOVE_HALF_ANGLE
+            {
+                continue;
+            }
+            let multiplier = damage.hit(SHOVE_DAMAGE);
+            let impulse = (SHOVE_IMPULSE + speed * SHOVE_VELOCITY_IMPULSE) * multiplier;
+            // lift a little so the victim does not grind along the ground
+            let direction = (flat.normalize_or_zero() + Vec3::Y * 0.3).normalize();
+            linear_velocity.0 += direction * impulse * inverse_mass.0;
+            knockback_event.send(KnockbackEvent {
+                attacker,
+                victim: entity,
+            });
+        }
+    }
+}
+
+/// Lets go when the grab input is released, the target is gone or the character respawns.
+fn release_grab(
+    mut commands: Commands,
+    mut respawn_event: EventReader<RespawnEvent>,
+    mut query: Query<(Entity, &Grabbing, &PlayerInput, &mut AbilityCooldowns)>,
+    body_query: Query<(), With<RigidBody>>,
+) {
+    let respawned: Vec<Entity> = respawn_event.read().map(|event| event.entity).collect();
+    for (entity, grabbing, input, mut cooldowns) in query.iter_mut() {
+        if input.grab && body_query.contains(grabbing.target) && !respawned.contains(&entity) {
+            continue;
+        }
+        commands.entity(grabbing.joint).despawn_recursive();
+        commands.entity(entity).remove::<Grabbing>();
+        cooldowns.start(Ability::Grab);
+    }
+}
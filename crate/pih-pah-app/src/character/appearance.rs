@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::util::asset_folder;
+
+use super::PLAYER_SIZE;
+
+/// Folder of the selectable character models, relative to the asset folder.
+pub const CHARACTER_MODEL_FOLDER: &str = "character";
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Hat {
+    #[default]
+    None,
+    TopHat,
+    Cap,
+    Halo,
+}
+
+impl Display for Hat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hat::None => write!(f, "None"),
+            Hat::TopHat => write!(f, "Top hat"),
+            Hat::Cap => write!(f, "Cap"),
+            Hat::Halo => write!(f, "Halo"),
+        }
+    }
+}
+
+impl Hat {
+    pub fn all() -> [Hat; 4] {
+        [Hat::None, Hat::TopHat, Hat::Cap, Hat::Halo]
+    }
+
+    fn mesh(&self) -> Option<Mesh> {
+        match self {
+            Hat::None => None,
+            Hat::TopHat => Some(Mesh::from(shape::Cylinder {
+                radius: PLAYER_SIZE * 0.2,
+                height: PLAYER_SIZE * 0.4,
+                resolution: 16,
+                segments: 1,
+            })),
+            Hat::Cap => Some(Mesh::from(shape::Cylinder {
+                radius: PLAYER_SIZE * 0.4,
+                height: PLAYER_SIZE * 0.1,
+                resolution: 16,
+                segments: 1,
+            })),
+            Hat::Halo => Some(Mesh::from(shape::Torus {
+                radius: PLAYER_SIZE * 0.3,
+                ring_radius: PLAYER_SIZE * 0.04,
+                ..default()
+            })),
+        }
+    }
+
+    /// Offset above the top of the character.
+    fn offset(&self) -> f32 {
+        match self {
+            Hat::None | Hat::Cap => PLAYER_SIZE * 0.05,
+            Hat::TopHat => PLAYER_SIZE * 0.2,
+            Hat::Halo => PLAYER_SIZE * 0.3,
+        }
+    }
+}
+
+/// Look of a character, chosen in the customization screen and persisted in settings.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Component, Serialize, Deserialize)]
+pub struct Appearance {
+    /// File in the character model folder, the plain cube when `None`
+    pub model: Option<String>,
+    pub hat: Hat,
+}
+
+/// Body color and shading of a character, set at spawn.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct BodyColor {
+    pub color: Color,
+    pub unlit: bool,
+}
+
+/// Child holding the meshes or scene built from the `Appearance`.
+#[derive(Debug, Component)]
+pub struct AppearanceVisual;
+
+/// Character models found in the character model folder at startup.
+#[derive(Debug, Default, Resource)]
+pub struct CharacterModels(pub Vec<String>);
+
+impl CharacterModels {
+    fn scan() -> Self {
+        let folder = asset_folder(CHARACTER_MODEL_FOLDER);
+        let Ok(entries) = std::fs::read_dir(&folder) else {
+            return Self::default();
+        };
+        let mut models: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".glb") || name.ends_with(".gltf"))
+            .collect();
+        models.sort();
+        Self(models)
+    }
+
+    pub fn contains(&self, model: &str) -> bool {
+        self.0.iter().any(|known| known == model)
+    }
+}
+
+/// Meshes, materials and scenes shared by every character.
+#[derive(Debug, Default, Resource)]
+pub struct AppearanceCache {
+    body: Option<Handle<Mesh>>,
+    hats: HashMap<Hat, Handle<Mesh>>,
+    materials: HashMap<([u32; 4], bool), Handle<StandardMaterial>>,
+    models: HashMap<String, Handle<Scene>>,
+}
+
+impl AppearanceCache {
+    pub fn body(&mut self, meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+        self.body
+            .get_or_insert_with(|| meshes.add(Mesh::from(shape::Cube { size: PLAYER_SIZE })))
+            .clone()
+    }
+
+    pub fn hat(&mut self, hat: Hat, meshes: &mut Assets<Mesh>) -> Option<Handle<Mesh>> {
+        if !self.hats.contains_key(&hat) {
+            let mesh = hat.mesh()?;
+            self.hats.insert(hat, meshes.add(mesh));
+        }
+        self.hats.get(&hat).cloned()
+    }
+
+    pub fn material(
+        &mut self,
+        body_color: BodyColor,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Handle<StandardMaterial> {
+        let key = (
+            body_color.color.as_rgba_f32().map(f32::to_bits),
+            body_color.unlit,
+        );
+        self.materials
+            .entry(key)
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: body_color.color,
+                    unlit: body_color.unlit,
+                    ..default()
+                })
+            })
+            .clone()
+    }
+
+    pub fn model(&mut self, model: &str, asset_server: &AssetServer) -> Handle<Scene> {
+        self.models
+            .entry(model.to_string())
+            .or_insert_with(|| {
+                asset_server.load(format!("{}/{}#Scene0", CHARACTER_MODEL_FOLDER, model))
+            })
+            .clone()
+    }
+}
+
+pub struct AppearancePlugins;
+
+impl Plugin for AppearancePlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AppearanceCache>()
+            .insert_resource(CharacterModels::scan())
+            .add_systems(Update, build_appearance);
+    }
+}
+
+/// Rebuilds the visual child of characters whose appearance changed.
+fn build_appearance(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    models: Res<CharacterModels>,
+    mut cache: ResMut<AppearanceCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    character_query: Query<
+        (Entity, &Appearance, &BodyColor, Option<&Children>),
+        Changed<Appearance>,
+    >,
+    visual_query: Query<(), With<AppearanceVisual>>,
+) {
+    for (entity, appearance, body_color, children) in character_query.iter() {
+        if let Some(children) = children {
+            for child in children.iter() {
+                if visual_query.contains(*child) {
+                    commands.entity(*child).despawn_recursive();
+                }
+            }
+        }
+
+        let material = cache.material(*body_color, &mut materials);
+        let visual = commands
+            .spawn((SpatialBundle::default(), AppearanceVisual))
+            .with_children(|parent| {
+                // a model missing on this machine falls back to the cube
+                match appearance
+                    .model
+                    .as_deref()
+                    .filter(|model| models.contains(model))
+                {
+                    Some(model) => {
+                        parent.spawn(SceneBundle {
+                            scene: cache.model(model, &asset_server),
+                            transform: Transform::from_xyz(0., -PLAYER_SIZE * 0.5, 0.),
+                            ..default()
+                        });
+                    }
+                    None => {
+                        parent.spawn(PbrBundle {
+                            mesh: cache.body(&mut meshes),
+                            material: material.clone(),
+                            ..default()
+                        });
+                    }
+                }
+                if let Some(mesh) = cache.hat(appearance.hat, &mut meshes) {
+                    parent.spawn(PbrBundle {
+                        mesh,
+                        material,
+                        transform: Transform::from_xyz(
+                            0.,
+                            PLAYER_SIZE * 0.5 + appearance.hat.offset(),
+                            0.,
+                        ),
+                        ..default()
+                    });
+                }
+            })
+            .id();
+        commands.entity(entity).add_child(visual);
+    }
+}
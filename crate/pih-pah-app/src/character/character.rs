@@ -1,63 +1,163 @@
+use super::{
+    AbilityButtons, AbilityCooldowns, AbilityPlugins, Appearance, AppearancePlugins, BodyColor,
+    CharacterConfig, Damage, JumpConfig, KnockbackPlugins, ShotStats, Weapon, WeaponPlugins,
+};
 use crate::component::{AxisName, DespawnReason, Respawn, UntouchedTimerValue};
 use crate::extend_commands;
 use crate::lobby::Character;
-use crate::lobby::{LobbyState, PlayerId, PlayerInput, PlayerViewDirection};
-use crate::world::{Me, MyLayers};
+use crate::lobby::{LobbyState, Ping, PlayerId, PlayerInput, PlayerViewDirection};
+use crate::province::GravityOverride;
+use crate::settings::{InputAction, InputSources, Settings};
+use crate::world::{
+    CheckpointProgress, Me, Mover, MoverClock, MoverTrigger, MyLayers, RestartButton,
+};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::window::{CursorGrabMode, PrimaryWindow};
 use bevy::{ecs::system::EntityCommands, prelude::*};
 use bevy_xpbd_3d::prelude::*;
 use serde::{Deserialize, Serialize};
 
-pub const PLAYER_MOVE_SPEED: f32 = 0.07;
-pub const PLAYER_CAMERA_ROTATION_SPEED: f32 = 0.015;
 pub const PLAYER_SIZE: f32 = 2.0;
 
 #[derive(Component, Debug, Serialize, Deserialize)]
 pub struct TiedCamera(Entity);
 
+/// Tied camera detached from its target and steered by the local input, e.g. spectating.
 #[derive(Component, Debug)]
-struct JumpHelper {
-    last_viable_normal: Vec3,
+pub struct FreeCamera;
+
+pub const FREE_CAMERA_SPEED: f32 = 15.;
+
+/// Direction of the camera from its target at zero pitch.
+pub const TIED_CAMERA_OFFSET: Vec3 = Vec3::new(0., 10., 15.);
+pub const TIED_CAMERA_DISTANCE_MIN: f32 = 4.;
+pub const TIED_CAMERA_DISTANCE_MAX: f32 = 40.;
+const TIED_CAMERA_ZOOM_STEP: f32 = 1.5;
+/// Radius of the spherecast keeping the camera out of walls
+const TIED_CAMERA_RADIUS: f32 = 0.5;
+/// Spring sharpness, higher is stiffer
+const TIED_CAMERA_FOLLOW_SHARPNESS: f32 = 15.;
+const TIED_CAMERA_ROTATION_SHARPNESS: f32 = 30.;
+const TIED_CAMERA_BOOM_SHARPNESS: f32 = 5.;
+/// Eye height above the character center in first person
+const FIRST_PERSON_EYE_HEIGHT: f32 = PLAYER_SIZE * 0.25;
+
+/// Arm holding the tied camera behind its target.
+///
+/// `zoom` is the wanted length from the scroll wheel, `length` the current one after collisions.
+#[derive(Component, Debug)]
+pub struct CameraBoom {
+    pub zoom: f32,
+    pub length: f32,
+    pub first_person: bool,
+}
+
+impl Default for CameraBoom {
+    fn default() -> Self {
+        Self {
+            zoom: TIED_CAMERA_OFFSET.length(),
+            length: TIED_CAMERA_OFFSET.length(),
+            first_person: false,
+        }
+    }
+}
+
+/// Movement state of a simulated character.
+#[derive(Component, Debug, Default)]
+pub struct CharacterController {
+    /// Touching a surface within the max ground angle
+    pub grounded: bool,
+    /// Average normal of the ground contacts, zero in the air
+    pub ground_normal: Vec3,
+    /// Horizontal normal of the touched walls, zero without walls
+    pub wall_normal: Vec3,
+    /// Body stood on, if any
+    pub ground_entity: Option<Entity>,
+    /// Velocity of a mover stood on, the character moves relative to it
+    pub ground_velocity: Vec3,
+    /// Seconds since the character left the ground
+    pub air_time: f32,
+    /// Seconds since an unconsumed jump press
+    pub jump_buffer: Option<f32>,
+    /// Rising from a jump that can still be cut short
+    pub jumping: bool,
+    jump_held: bool,
 }
 
 pub struct CharacterPlugins;
 
 impl Plugin for CharacterPlugins {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            FixedUpdate,
-            move_characters.run_if(
-                not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
-            ),
-        )
-        .add_systems(
-            FixedUpdate,
-            jump.run_if(
-                not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
-            ),
-        )
-        .add_systems(
-            PostUpdate,
-            tied_camera_follow.run_if(not(in_state(LobbyState::None))),
-        )
-        .add_systems(
-            FixedUpdate,
-            update_jump_normals.run_if(
-                not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
-            ),
-        );
+        app.init_resource::<CharacterConfig>()
+            .init_resource::<JumpConfig>()
+            .add_plugins((
+                AbilityPlugins,
+                AppearancePlugins,
+                KnockbackPlugins,
+                WeaponPlugins,
+            ))
+            .add_systems(
+                FixedUpdate,
+                move_characters.after(update_grounded).run_if(
+                    not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                jump.after(update_grounded).run_if(
+                    not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (tied_camera_follow, place_tied_camera)
+                    .chain()
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(not(in_state(LobbyState::None))),
+            )
+            .add_systems(
+                Update,
+                (free_camera_move, zoom_tied_camera, toggle_first_person)
+                    .run_if(not(in_state(LobbyState::None))),
+            )
+            .add_systems(
+                FixedUpdate,
+                update_grounded.run_if(
+                    not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
+                ),
+            );
     }
 }
 
+/// Factor of an exponential spring step, independent of the frame rate.
+fn spring(sharpness: f32, dt: f32) -> f32 {
+    1. - (-sharpness * dt).exp()
+}
+
 fn tied_camera_follow(
-    mut tied_camera_query: Query<(&TiedCamera, &mut Transform)>,
-    view_direction_query: Query<&PlayerViewDirection, With<Me>>,
+    time: Res<Time>,
+    mut tied_camera_query: Query<(&TiedCamera, &CameraBoom, &mut Transform), Without<FreeCamera>>,
+    player_input_query: Query<&PlayerInput, With<Me>>,
     transform_query: Query<&Transform, Without<TiedCamera>>,
 ) {
-    for (TiedCamera(target), mut transform) in tied_camera_query.iter_mut() {
+    let dt = time.delta_seconds();
+    for (TiedCamera(target), boom, mut transform) in tied_camera_query.iter_mut() {
         if let Ok(target_transform) = transform_query.get(*target) {
-            transform.translation = target_transform.translation;
-            if let Ok(view_dirrection) = view_direction_query.get_single() {
-                transform.rotation = view_dirrection.0;
+            // the eye is rigid, only the third person view lags behind
+            transform.translation = if boom.first_person {
+                target_transform.translation
+            } else {
+                transform.translation.lerp(
+                    target_transform.translation,
+                    spring(TIED_CAMERA_FOLLOW_SHARPNESS, dt),
+                )
+            };
+            // the local input is ahead of the replicated view direction
+            if let Ok(input) = player_input_query.get_single() {
+                let look = PlayerViewDirection::from(input).look();
+                transform.rotation = transform
+                    .rotation
+                    .slerp(look, spring(TIED_CAMERA_ROTATION_SHARPNESS, dt));
             }
         } else {
             warn!(
@@ -68,141 +168,348 @@ fn tied_camera_follow(
     }
 }
 
-fn update_jump_normals(
-    mut query: Query<(&mut JumpHelper, Entity, &GlobalTransform)>,
+fn free_camera_move(
+    time: Res<Time>,
+    config: Res<CharacterConfig>,
+    mut free_camera_query: Query<&mut Transform, With<FreeCamera>>,
+    player_input_query: Query<&PlayerInput, With<Me>>,
+) {
+    let Ok(input) = player_input_query.get_single() else {
+        return;
+    };
+    for mut transform in free_camera_query.iter_mut() {
+        let movement = input.movement();
+
+        let speed = if input.sprint {
+            FREE_CAMERA_SPEED * config.sprint_multiplier
+        } else {
+            FREE_CAMERA_SPEED
+        };
+        transform.rotation = PlayerViewDirection::from(input).look();
+        let direction = transform
+            .rotation
+            .mul_vec3(Vec3::new(movement.x, 0., movement.y))
+            + Vec3::Y * input.jump as i8 as f32;
+        transform.translation += direction.clamp_length_max(1.) * speed * time.delta_seconds();
+    }
+}
+
+fn zoom_tied_camera(
+    mut mouse_wheel: EventReader<MouseWheel>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut boom_query: Query<&mut CameraBoom>,
+) {
+    // scrolling menus must not zoom
+    let grabbed = windows
+        .get_single()
+        .is_ok_and(|window| window.cursor.grab_mode != CursorGrabMode::None);
+    let lines: f32 = mouse_wheel
+        .read()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => wheel.y,
+            MouseScrollUnit::Pixel => wheel.y / 16.,
+        })
+        .sum();
+    if !grabbed || lines == 0. {
+        return;
+    }
+
+    for mut boom in boom_query.iter_mut() {
+        boom.zoom = (boom.zoom - lines * TIED_CAMERA_ZOOM_STEP)
+            .clamp(TIED_CAMERA_DISTANCE_MIN, TIED_CAMERA_DISTANCE_MAX);
+    }
+}
+
+fn toggle_first_person(
+    input: InputSources,
+    settings: Res<Settings>,
+    mut boom_query: Query<&mut CameraBoom>,
+) {
+    if settings
+        .input_map
+        .just_pressed(InputAction::ToggleCamera, &input)
+    {
+        for mut boom in boom_query.iter_mut() {
+            boom.first_person = !boom.first_person;
+        }
+    }
+}
+
+/// Pulls the camera in front of solid geometry between it and its target,
+/// sensor volumes and characters do not occlude.
+fn place_tied_camera(
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    character_query: Query<Entity, With<Character>>,
+    mut tied_camera_query: Query<(&TiedCamera, &mut CameraBoom, &Transform, &Children)>,
+    mut camera_query: Query<&mut Transform, (With<Camera3d>, Without<TiedCamera>)>,
+) {
+    let dt = time.delta_seconds();
+    for (TiedCamera(target), mut boom, transform, children) in tied_camera_query.iter_mut() {
+        let camera_transform = if boom.first_person {
+            Transform::from_translation(Vec3::Y * FIRST_PERSON_EYE_HEIGHT)
+        } else {
+            let direction = transform.rotation.mul_vec3(TIED_CAMERA_OFFSET.normalize());
+            let free_length = spatial_query
+                .cast_shape(
+                    &Collider::ball(TIED_CAMERA_RADIUS),
+                    transform.translation,
+                    Quat::IDENTITY,
+                    direction,
+                    boom.zoom,
+                    true,
+                    SpatialQueryFilter::new()
+                        .with_masks([MyLayers::Default])
+                        .without_entities(character_query.iter().chain([*target])),
+                )
+                .map_or(boom.zoom, |hit| hit.time_of_impact);
+
+            // snap in when occluded, ease back out when clear
+            boom.length = if free_length < boom.length {
+                free_length
+            } else {
+                boom.length + (free_length - boom.length) * spring(TIED_CAMERA_BOOM_SHARPNESS, dt)
+            };
+            let mut camera_transform =
+                Transform::from_translation(TIED_CAMERA_OFFSET).looking_at(Vec3::ZERO, Vec3::Y);
+            camera_transform.translation = TIED_CAMERA_OFFSET.normalize() * boom.length;
+            camera_transform
+        };
+
+        for child in children.iter() {
+            if let Ok(mut child_transform) = camera_query.get_mut(*child) {
+                *child_transform = camera_transform;
+            }
+        }
+    }
+}
+
+/// Sorts the contacts of every character into ground and walls, relative to its gravity.
+pub(super) fn update_grounded(
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    jump_config: Res<JumpConfig>,
     collisions: Res<Collisions>,
+    clock: Res<MoverClock>,
+    mut query: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut CharacterController,
+        Option<&GravityOverride>,
+    )>,
+    mover_query: Query<(&Mover, Option<&MoverTrigger>)>,
+    sensor_query: Query<(), With<Sensor>>,
 ) {
-    let mut direction_vector = Vec3::ZERO;
-    for (mut jump_direction, player_entity, transform) in query.iter_mut() {
-        for collision in collisions.collisions_with_entity(player_entity) {
+    for (entity, transform, mut controller, gravity_override) in query.iter_mut() {
+        let up = (-GravityOverride::resolve(gravity_override, gravity.0))
+            .try_normalize()
+            .unwrap_or(Vec3::Y);
+        let mut ground_normal = Vec3::ZERO;
+        let mut wall_normal = Vec3::ZERO;
+        let mut ground_entity = None;
+        for collision in collisions.collisions_with_entity(entity) {
+            let other = if collision.entity1 == entity {
+                collision.entity2
+            } else {
+                collision.entity1
+            };
+            // standing in a trigger or zone is not standing on it
+            if sensor_query.contains(other) {
+                continue;
+            }
             for manifold in collision.manifolds.iter() {
-                for contact in manifold.contacts.iter() {
-                    direction_vector += transform.translation() - contact.point1;
+                // contacts of a face sit on its corners, their sum points along the face normal
+                let normal = manifold
+                    .contacts
+                    .iter()
+                    .map(|contact| transform.translation() - contact.point1)
+                    .sum::<Vec3>()
+                    .normalize_or_zero();
+                if jump_config.is_ground(normal, up) {
+                    ground_normal += normal;
+                    ground_entity = Some(other);
+                } else if normal.dot(up).abs() < 0.5 {
+                    wall_normal += normal;
                 }
             }
         }
-        jump_direction.last_viable_normal = direction_vector.normalize_or_zero();
+
+        let grounded = ground_normal != Vec3::ZERO;
+        controller.ground_normal = ground_normal.normalize_or_zero();
+        controller.wall_normal = (wall_normal - up * wall_normal.dot(up)).normalize_or_zero();
+        controller.ground_entity = ground_entity;
+        controller.ground_velocity = ground_entity
+            .and_then(|ground| mover_query.get(ground).ok())
+            .map_or(Vec3::ZERO, |(mover, trigger)| {
+                mover.velocity_at(clock.0, trigger, transform.translation())
+            });
+        controller.air_time = if grounded {
+            0.
+        } else {
+            controller.air_time + time.delta_seconds()
+        };
+        if controller.grounded != grounded {
+            controller.grounded = grounded;
+        }
     }
 }
 
 fn jump(
-    mut query: Query<(&mut LinearVelocity, &PlayerInput, Entity, &JumpHelper)>, /* , time: Res<Time> */
+    time: Res<Time>,
     gravity: Res<Gravity>,
-    collisions: Res<Collisions>,
+    jump_config: Res<JumpConfig>,
+    mut query: Query<(
+        &mut LinearVelocity,
+        &PlayerInput,
+        &mut CharacterController,
+        Option<&GravityOverride>,
+    )>,
 ) {
-    for (mut linear_velocity, input, player_entity, jump_direction) in query.iter_mut() {
-        let jumped = input.jump;
-        if jumped
-            && collisions
-                .collisions_with_entity(player_entity)
-                .next()
-                .is_some()
-        {
-            **linear_velocity +=
-                jump_direction.last_viable_normal * (-gravity.0.y * 2.0 * PLAYER_SIZE).sqrt() * 1.1; // sqrt(2gh)
-            log::info!("{:?}", jump_direction.last_viable_normal);
+    let dt = time.delta_seconds();
+    for (mut linear_velocity, input, mut controller, gravity_override) in query.iter_mut() {
+        let body_gravity = GravityOverride::resolve(gravity_override, gravity.0);
+        let up = (-body_gravity).try_normalize().unwrap_or(Vec3::Y);
+        // without gravity the jump of the province gravity still pushes off
+        let speed = jump_config.speed(if body_gravity == Vec3::ZERO {
+            gravity.0.length()
+        } else {
+            body_gravity.length()
+        });
+        // the held state survives lost or repeated input messages, unlike a press flag
+        let pressed = input.jump && !controller.jump_held;
+        controller.jump_held = input.jump;
+        controller.jump_buffer = if pressed {
+            Some(0.)
+        } else {
+            controller
+                .jump_buffer
+                .map(|age| age + dt)
+                .filter(|age| *age <= jump_config.buffer_time)
+        };
+
+        let rising = linear_velocity.dot(up);
+        if controller.jumping && rising <= 0. {
+            controller.jumping = false;
+        }
+
+        if controller.jump_buffer.is_some() {
+            let can_jump = controller.air_time <= jump_config.coyote_time && !controller.jumping;
+            let can_wall_jump = jump_config.wall_jump
+                && !controller.grounded
+                && controller.wall_normal != Vec3::ZERO;
+            if can_jump || can_wall_jump {
+                // falling speed must not eat the jump
+                let mut velocity = linear_velocity.0 - up * rising.min(0.) + up * speed;
+                if !can_jump {
+                    velocity += controller.wall_normal * jump_config.wall_jump_push;
+                }
+                linear_velocity.0 = velocity;
+                controller.jump_buffer = None;
+                controller.jumping = true;
+                continue;
+            }
+        }
+
+        // releasing the key early cuts the jump short
+        if controller.jumping && !input.jump {
+            linear_velocity.0 -= up * rising * (1. - jump_config.release_factor);
+            controller.jumping = false;
         }
     }
 }
 
 fn move_characters(
-    mut query: Query<(&mut LinearVelocity, &mut PlayerViewDirection, &PlayerInput)>, /* , time: Res<Time> */
+    time: Res<Time>,
+    config: Res<CharacterConfig>,
+    mut query: Query<(
+        &mut LinearVelocity,
+        &mut PlayerViewDirection,
+        &PlayerInput,
+        &CharacterController,
+    )>,
 ) {
-    for (mut linear_velocity, mut view_direction, input) in query.iter_mut() {
-        let dx = (input.right as i8 - input.left as i8) as f32;
-        let dy = (input.down as i8 - input.up as i8) as f32;
+    // `Time` is the fixed clock inside FixedUpdate
+    let dt = time.delta_seconds();
+    for (mut linear_velocity, mut view_direction, input, controller) in query.iter_mut() {
+        let movement = input.movement();
+
+        *view_direction = PlayerViewDirection::from(input);
 
         // convert axises to global
-        let global_x = view_direction.0.mul_vec3(Vec3::X);
-        let global_y = view_direction.0.mul_vec3(Vec3::Z);
-
-        // never use delta time in fixed update !!!
-
-        // move by x axis
-        linear_velocity.x +=
-            dx * PLAYER_MOVE_SPEED * global_x.x * 1.5_f32.powf(input.sprint as i32 as f32); // * time.delta().as_secs_f32();
-        linear_velocity.z +=
-            dx * PLAYER_MOVE_SPEED * global_x.z * 1.5_f32.powf(input.sprint as i32 as f32); // * time.delta().as_secs_f32();
-
-        // move by y axis
-        linear_velocity.x +=
-            dy * PLAYER_MOVE_SPEED * global_y.x * 1.5_f32.powf(input.sprint as i32 as f32); // * time.delta().as_secs_f32();
-        linear_velocity.z +=
-            dy * PLAYER_MOVE_SPEED * global_y.z * 1.5_f32.powf(input.sprint as i32 as f32); // * time.delta().as_secs_f32();
-
-        // camera turn
-        let turn = (input.turn_right as i8 - input.turn_left as i8) as f32;
-
-        let rotation = Quat::from_rotation_y(
-            PLAYER_CAMERA_ROTATION_SPEED * turn, /* * delta_seconds */
-        );
-        view_direction.0 *= rotation;
+        let global_x = view_direction.rotation().mul_vec3(Vec3::X);
+        let global_y = view_direction.rotation().mul_vec3(Vec3::Z);
+        let wish = Vec2::new(global_x.x, global_x.z) * movement.x
+            + Vec2::new(global_y.x, global_y.z) * movement.y;
+
+        let grounded = controller.grounded;
+        // movement is relative to the mover stood on
+        let carry = Vec2::new(controller.ground_velocity.x, controller.ground_velocity.z);
+        let velocity = Vec2::new(linear_velocity.x, linear_velocity.z) - carry;
+        let velocity = if wish != Vec2::ZERO {
+            // a half tilted stick walks at half speed
+            let max_speed = config.max_speed(grounded, input.sprint) * wish.length().min(1.);
+            let wish = wish.normalize();
+            let acceleration = config.acceleration(grounded, velocity.dot(wish), max_speed);
+            move_towards(velocity, wish * max_speed, acceleration * dt)
+        } else {
+            move_towards(velocity, Vec2::ZERO, config.friction(grounded) * dt)
+        };
+        let velocity = velocity + carry;
+        linear_velocity.x = velocity.x;
+        linear_velocity.z = velocity.y;
     }
 }
 
-extend_commands!(
-  spawn_character(player_id: PlayerId, color: Color, spawn_point: Vec3),
-  |world: &mut World, entity_id: Entity, player_id: PlayerId, color: Color, spawn_point: Vec3| {
+fn move_towards(current: Vec2, target: Vec2, max_delta: f32) -> Vec2 {
+    let delta = target - current;
+    if delta.length() <= max_delta {
+        target
+    } else {
+        current + delta.normalize() * max_delta
+    }
+}
 
-    let mesh = world
-      .resource_mut::<Assets<Mesh>>()
-      // TODO: Have a resource with shared mesh list instead of adding meshes each time
-      .add(Mesh::from(shape::Cube { size: PLAYER_SIZE }));
-    let material = world
-      .resource_mut::<Assets<StandardMaterial>>()
-      .add(color.into());
+extend_commands!(
+  spawn_character(player_id: PlayerId, color: Color, appearance: Appearance, spawn_point: Vec3),
+  |world: &mut World, entity_id: Entity, player_id: PlayerId, color: Color, appearance: Appearance, spawn_point: Vec3| {
 
     world
      .entity_mut(entity_id)
      .insert((
-       PbrBundle {
-          mesh,
-          material,
-          ..Default::default()
-       },
+       SpatialBundle::default(),
+       // meshes are shared through the `AppearanceCache`
+       appearance,
+       BodyColor { color, unlit: false },
        Friction::new(0.4),
        RigidBody::Dynamic,
        Position::from_xyz(spawn_point.x, spawn_point.y, spawn_point.z),
        Collider::cuboid(PLAYER_SIZE, PLAYER_SIZE, PLAYER_SIZE),
-       JumpHelper{last_viable_normal: Vec3::Y},
-       CollisionLayers::new([MyLayers::Default], [MyLayers::Default, MyLayers::ActorNoclip]),
+       CharacterController::default(),
+       CollisionLayers::new([MyLayers::Default], [MyLayers::Default, MyLayers::ActorNoclip, MyLayers::Sensor]),
      ))
      .insert(Respawn::new(DespawnReason::Less(-10., AxisName::Y), spawn_point, UntouchedTimerValue::Timer(10.)))
      .insert(PlayerInput::default())
      .insert(Character { id: player_id })
-     .insert(PlayerViewDirection(Quat::default()));
+     .insert(PlayerViewDirection::default())
+     .insert((AbilityCooldowns::default(), AbilityButtons::default(), Damage::default(), Ping::default(), CheckpointProgress::default(), RestartButton::default()))
+     .insert((Weapon::default(), ShotStats::default()));
   }
 );
 
 extend_commands!(
-  spawn_character_shell(color: Color, spawn_point: Vec3),
-  |world: &mut World, entity_id: Entity, color: Color, spawn_point: Vec3| {
-
-    let mesh = world
-      .resource_mut::<Assets<Mesh>>()
-      // TODO: Have a resource with shared mesh list instead of adding meshes each time
-      .add(Mesh::from(shape::Cube { size: PLAYER_SIZE }));
-    let material = StandardMaterial {
-      unlit: true,
-      base_color: color,
-      ..default()
-    };
-    let material = world
-      .resource_mut::<Assets<StandardMaterial>>()
-      .add(material);
+  spawn_character_shell(color: Color, appearance: Appearance, spawn_point: Vec3),
+  |world: &mut World, entity_id: Entity, color: Color, appearance: Appearance, spawn_point: Vec3| {
 
     world
      .entity_mut(entity_id)
      .insert((
-       PbrBundle {
-          mesh,
-          material,
-          transform: Transform::from_xyz(spawn_point.x, spawn_point.y, spawn_point.z),
-          ..Default::default()
-       },
+       SpatialBundle::from_transform(Transform::from_xyz(spawn_point.x, spawn_point.y, spawn_point.z)),
+       appearance,
+       BodyColor { color, unlit: true },
      ))
      .insert(PlayerInput::default())
-     .insert(PlayerViewDirection(Quat::default()));
+     .insert(PlayerViewDirection::default())
+     .insert((AbilityCooldowns::default(), Damage::default(), Ping::default()));
   }
 );
 
@@ -215,12 +522,13 @@ extend_commands!(
         // TODO find light prd without mesh
         PbrBundle::default(),
         TiedCamera(target),
+        CameraBoom::default(),
         Name::new("TiedCamera"),
       ))
       .with_children(|parent| {
         // spawn tied camera
         parent.spawn(Camera3dBundle {
-          transform: Transform::from_xyz(0., 10., 15.).looking_at(Vec3::ZERO, Vec3::Y),
+          transform: Transform::from_translation(TIED_CAMERA_OFFSET).looking_at(Vec3::ZERO, Vec3::Y),
           ..Default::default()
         });
       });
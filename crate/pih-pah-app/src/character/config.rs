@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::PLAYER_SIZE;
+
+/// Movement tuning shared by every character, owned by the host and replicated to clients.
+///
+/// Speeds are in m/s, accelerations and friction in m/s².
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+pub struct CharacterConfig {
+    pub max_ground_speed: f32,
+    pub max_air_speed: f32,
+    pub sprint_multiplier: f32,
+    pub ground_acceleration: f32,
+    pub air_acceleration: f32,
+    /// Acceleration fades as the speed gets close to max, `0` keeps it constant
+    pub acceleration_curve: f32,
+    /// Deceleration without input
+    pub ground_friction: f32,
+    pub air_friction: f32,
+    /// View turn speed in rad/s
+    pub turn_speed: f32,
+}
+
+impl Default for CharacterConfig {
+    fn default() -> Self {
+        Self {
+            max_ground_speed: 12.,
+            max_air_speed: 12.,
+            sprint_multiplier: 1.5,
+            ground_acceleration: 40.,
+            air_acceleration: 10.,
+            acceleration_curve: 1.,
+            ground_friction: 30.,
+            air_friction: 1.,
+            turn_speed: 0.96,
+        }
+    }
+}
+
+impl CharacterConfig {
+    pub fn max_speed(&self, grounded: bool, sprint: bool) -> f32 {
+        let max_speed = if grounded {
+            self.max_ground_speed
+        } else {
+            self.max_air_speed
+        };
+        if sprint {
+            max_speed * self.sprint_multiplier
+        } else {
+            max_speed
+        }
+    }
+
+    /// Acceleration at `speed` towards a `max_speed` target.
+    pub fn acceleration(&self, grounded: bool, speed: f32, max_speed: f32) -> f32 {
+        let acceleration = if grounded {
+            self.ground_acceleration
+        } else {
+            self.air_acceleration
+        };
+        if self.acceleration_curve <= 0. {
+            return acceleration;
+        }
+        let progress = (speed / max_speed.max(f32::EPSILON)).clamp(0., 1.);
+        // keep a little push so the target is still reached
+        acceleration * (1. - progress.powf(self.acceleration_curve) * 0.8)
+    }
+
+    pub fn friction(&self, grounded: bool) -> f32 {
+        if grounded {
+            self.ground_friction
+        } else {
+            self.air_friction
+        }
+    }
+}
+
+/// Ground check and jump tuning, every province inserts the one of its manifest on load.
+///
+/// Only the host simulates characters, so it is not replicated.
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JumpConfig {
+    /// Steeper contacts are walls, characters slide down them
+    pub max_ground_angle_degrees: f32,
+    /// Apex of a full jump in meters
+    pub height: f32,
+    /// Seconds after leaving the ground a jump is still allowed
+    pub coyote_time: f32,
+    /// Seconds a jump press is remembered before landing
+    pub buffer_time: f32,
+    /// Rising speed kept when jump is released early, `1` disables variable height
+    pub release_factor: f32,
+    /// Jump off walls while airborne
+    pub wall_jump: bool,
+    /// Speed away from the wall of a wall jump
+    pub wall_jump_push: f32,
+}
+
+impl Default for JumpConfig {
+    fn default() -> Self {
+        Self {
+            max_ground_angle_degrees: 45.,
+            height: PLAYER_SIZE * 1.2,
+            coyote_time: 0.12,
+            buffer_time: 0.15,
+            release_factor: 0.5,
+            wall_jump: false,
+            wall_jump_push: 10.,
+        }
+    }
+}
+
+impl JumpConfig {
+    /// Whether a contact normal is walkable, `up` being opposite to the gravity of the character.
+    pub fn is_ground(&self, normal: Vec3, up: Vec3) -> bool {
+        normal != Vec3::ZERO
+            && normal.angle_between(up) <= self.max_ground_angle_degrees.to_radians()
+    }
+
+    /// Initial speed reaching `height` under `gravity`, sqrt(2gh).
+    pub fn speed(&self, gravity: f32) -> f32 {
+        (2. * gravity.abs() * self.height).sqrt()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::component::RespawnEvent;
+use crate::lobby::{LobbyState, PlayerId};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Damage dealt by a shove, in percent
+pub const SHOVE_DAMAGE: f32 = 12.;
+pub const GROUND_POUND_DAMAGE: f32 = 8.;
+
+/// Damage taken since the last respawn in percent, knockback grows with it like in a platform fighter.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component, Serialize, Deserialize)]
+pub struct Damage(pub f32);
+
+impl Damage {
+    pub fn knockback_multiplier(&self) -> f32 {
+        1. + self.0 / 100.
+    }
+
+    /// Adds `damage` and returns the multiplier of this hit.
+    pub fn hit(&mut self, damage: f32) -> f32 {
+        let multiplier = self.knockback_multiplier();
+        self.0 += damage;
+        multiplier
+    }
+}
+
+/// Sent by the server whenever a player knocks another character back, for knockout credit.
+#[derive(Debug, Event)]
+pub struct KnockbackEvent {
+    pub attacker: PlayerId,
+    pub victim: Entity,
+}
+
+pub struct KnockbackPlugins;
+
+impl Plugin for KnockbackPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_event::<KnockbackEvent>().add_systems(
+            Update,
+            reset_damage.run_if(
+                not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
+            ),
+        );
+    }
+}
+
+fn reset_damage(
+    mut respawn_event: EventReader<RespawnEvent>,
+    mut damage_query: Query<&mut Damage>,
+) {
+    for RespawnEvent { entity, .. } in respawn_event.read() {
+        if let Ok(mut damage) = damage_query.get_mut(*entity) {
+            if damage.0 != 0. {
+                damage.0 = 0.;
+            }
+        }
+    }
+}
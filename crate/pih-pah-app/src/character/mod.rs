@@ -1,4 +1,14 @@
 #![allow(clippy::module_inception)]
 
+mod ability;
+mod appearance;
 mod character;
+mod config;
+mod knockback;
+mod weapon;
+pub use ability::*;
+pub use appearance::*;
 pub use character::*;
+pub use config::*;
+pub use knockback::*;
+pub use weapon::*;
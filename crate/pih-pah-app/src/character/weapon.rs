@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_mode::ScoreEvent;
+use crate::lobby::{Character, LobbyState, PlayerInput, PlayerViewDirection};
+use crate::province::ProvinceState;
+use crate::settings::Settings;
+use crate::sound::SoundEffects;
+use crate::world::{Me, MyLayers, Target, TargetState};
+
+use super::character::update_grounded;
+use super::{TiedCamera, PLAYER_SIZE, TIED_CAMERA_DISTANCE_MAX};
+
+/// Seconds the hit marker stays on screen.
+pub const HIT_MARKER_TIME: f32 = 0.3;
+/// Largest angle in radians between the aim and the view heading the host accepts
+const MAX_AIM_DEVIATION: f32 = 0.6;
+/// Farthest the camera may be from the character, with slack for its smoothing
+const MAX_AIM_ORIGIN_DISTANCE: f32 = TIED_CAMERA_DISTANCE_MAX + 2. * PLAYER_SIZE;
+
+/// Hitscan gun of a province, characters are unarmed in provinces without it.
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeaponConfig {
+    /// Meters a shot reaches
+    pub range: f32,
+    /// Seconds between shots
+    pub cooldown: f32,
+    /// Push of a shot on a dynamic body
+    pub impulse: f32,
+}
+
+impl Default for WeaponConfig {
+    fn default() -> Self {
+        Self {
+            range: 100.,
+            cooldown: 0.3,
+            impulse: 8.,
+        }
+    }
+}
+
+/// Seconds before the character can shoot again.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct Weapon {
+    pub cooldown: f32,
+}
+
+/// Shots of a character in the loaded province, counted by the host and replicated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component, Serialize, Deserialize)]
+pub struct ShotStats {
+    pub shots: u32,
+    pub hits: u32,
+    pub points: u32,
+}
+
+impl ShotStats {
+    /// Share of the shots that hit a target, `None` before the first shot.
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.shots > 0).then(|| self.hits as f32 / self.shots as f32)
+    }
+}
+
+/// Shots of the local player, followed for the hit marker.
+#[derive(Debug, Resource)]
+pub struct MyShots {
+    pub stats: ShotStats,
+    /// Runs while the hit marker is shown
+    pub marker: Timer,
+}
+
+impl Default for MyShots {
+    fn default() -> Self {
+        let mut marker = Timer::from_seconds(HIT_MARKER_TIME, TimerMode::Once);
+        marker.tick(marker.duration());
+        Self {
+            stats: ShotStats::default(),
+            marker,
+        }
+    }
+}
+
+pub struct WeaponPlugins;
+
+impl Plugin for WeaponPlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MyShots>()
+            .add_systems(
+                FixedUpdate,
+                fire.after(update_grounded).run_if(
+                    not(in_state(LobbyState::None))
+                        .and_then(not(in_state(LobbyState::Client)))
+                        .and_then(resource_exists::<WeaponConfig>()),
+                ),
+            )
+            .add_systems(
+                Update,
+                reset_stats.run_if(
+                    state_changed::<ProvinceState>().and_then(not(in_state(LobbyState::Client))),
+                ),
+            )
+            .add_systems(
+                Update,
+                (aim, follow_my_shots).run_if(not(in_state(LobbyState::None))),
+            );
+    }
+}
+
+/// The local player aims through the crosshair at the center of its camera.
+fn aim(
+    tied_camera_query: Query<&Children, With<TiedCamera>>,
+    camera_query: Query<&GlobalTransform, With<Camera3d>>,
+    mut player_input_query: Query<&mut PlayerInput, With<Me>>,
+) {
+    let Ok(mut player_input) = player_input_query.get_single_mut() else {
+        return;
+    };
+    let Some(camera_transform) = tied_camera_query
+        .iter()
+        .flat_map(|children| children.iter())
+        .find_map(|child| camera_query.get(*child).ok())
+    else {
+        return;
+    };
+    player_input.aim = camera_transform.forward();
+    player_input.aim_origin = camera_transform.translation();
+}
+
+/// Aim sent by the player, the view direction when it is missing or does not match the view.
+fn validated_aim(input: &PlayerInput) -> Vec3 {
+    let view = PlayerViewDirection::from(input);
+    let fallback = view.look().mul_vec3(Vec3::NEG_Z);
+    let Some(aim) = input.aim.try_normalize() else {
+        return fallback;
+    };
+    // nearly vertical aims have no heading to compare
+    let flat = Vec3::new(aim.x, 0., aim.z);
+    if flat.length() > 0.1
+        && flat.angle_between(view.rotation().mul_vec3(Vec3::NEG_Z)) > MAX_AIM_DEVIATION
+    {
+        return fallback;
+    }
+    aim
+}
+
+/// Camera the player aimed from, the character itself when it is missing or too far away.
+fn validated_aim_origin(input: &PlayerInput, position: Vec3) -> Vec3 {
+    if input.aim_origin.is_finite()
+        && input.aim_origin.distance(position) <= MAX_AIM_ORIGIN_DISTANCE
+    {
+        input.aim_origin
+    } else {
+        position
+    }
+}
+
+/// Point under the crosshair, the shot flies there from the character.
+fn crosshair_point(
+    spatial_query: &SpatialQuery,
+    filter: SpatialQueryFilter,
+    origin: Vec3,
+    direction: Vec3,
+    position: Vec3,
+    range: f32,
+) -> Vec3 {
+    // from abreast of the character, what is between it and the camera is not aimed at
+    let start = origin + direction * (position - origin).dot(direction).max(0.);
+    let distance = spatial_query
+        .cast_ray(start, direction, range, true, filter)
+        .map_or(range, |hit| hit.time_of_impact);
+    start + direction * distance
+}
+
+/// Casts the shot from the character toward the crosshair on the host,
+/// only targets standing up count as hits. Sensor volumes do not stop shots.
+fn fire(
+    time: Res<Time>,
+    config: Res<WeaponConfig>,
+    spatial_query: SpatialQuery,
+    mut score_event: EventWriter<ScoreEvent>,
+    mut shooter_query: Query<(
+        Entity,
+        &Character,
+        &Position,
+        &PlayerInput,
+        &mut Weapon,
+        &mut ShotStats,
+    )>,
+    mut target_query: Query<(&Target, &mut TargetState)>,
+    mut body_query: Query<(&RigidBody, &InverseMass, &mut LinearVelocity), Without<Character>>,
+) {
+    for (entity, character, position, input, mut weapon, mut stats) in shooter_query.iter_mut() {
+        weapon.cooldown = (weapon.cooldown - time.delta_seconds()).max(0.);
+        if !input.fire || weapon.cooldown > 0. {
+            continue;
+        }
+        weapon.cooldown = config.cooldown;
+        stats.shots += 1;
+
+        let filter = || {
+            SpatialQueryFilter::new()
+                .with_masks([MyLayers::Default, MyLayers::ActorNoclip])
+                .without_entities([entity])
+        };
+        let aim = validated_aim(input);
+        let origin = validated_aim_origin(input, position.0);
+        let point = crosshair_point(
+            &spatial_query,
+            filter(),
+            origin,
+            aim,
+            position.0,
+            config.range,
+        );
+        let direction = (point - position.0).try_normalize().unwrap_or(aim);
+        let Some(hit) = spatial_query.cast_ray(position.0, direction, config.range, true, filter())
+        else {
+            continue;
+        };
+
+        if let Ok((rigid_body, inverse_mass, mut linear_velocity)) = body_query.get_mut(hit.entity)
+        {
+            if rigid_body.is_dynamic() {
+                linear_velocity.0 += direction * config.impulse * inverse_mass.0;
+            }
+        }
+        let Ok((target, mut state)) = target_query.get_mut(hit.entity) else {
+            continue;
+        };
+        if !state.hit() {
+            continue;
+        }
+        stats.hits += 1;
+        stats.points += target.points;
+        score_event.send(ScoreEvent {
+            player_id: character.id,
+            points: target.points as i32,
+        });
+    }
+}
+
+fn reset_stats(mut stats_query: Query<&mut ShotStats>) {
+    for mut stats in stats_query.iter_mut() {
+        *stats = ShotStats::default();
+    }
+}
+
+fn follow_my_shots(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+    effects: Res<SoundEffects>,
+    mut my_shots: ResMut<MyShots>,
+    stats_query: Query<&ShotStats, With<Me>>,
+) {
+    my_shots.marker.tick(time.delta());
+    let stats = stats_query.get_single().copied().unwrap_or_default();
+    if stats == my_shots.stats {
+        return;
+    }
+    if stats.hits > my_shots.stats.hits {
+        effects.play(&audio, &settings, &effects.hit);
+        my_shots.marker.reset();
+    }
+    my_shots.stats = stats;
+}
@@ -1,5 +1,6 @@
 use bevy::app::{App, PreUpdate, Update};
 use bevy::ecs::entity::Entity;
+use bevy::ecs::event::{Event, EventWriter};
 use bevy::ecs::query::With;
 use bevy::ecs::system::{Commands, Query, Res};
 use bevy::log::info;
@@ -17,6 +18,8 @@ use super::despawn_type::{DespawnReason, IntoDespawnTypeVec};
 pub struct Respawn {
     reason: Vec<DespawnReason>,
     spawn_point: Vec3,
+    /// Where the entity was first spawned, checkpoints only move `spawn_point`
+    start_point: Vec3,
     untuched_on_spawn: UntouchedTimerValue,
 }
 
@@ -29,6 +32,20 @@ pub enum UntouchedTimerValue {
 #[derive(Deref, DerefMut, Component)]
 pub struct UntouchedTimer(Timer);
 
+/// Entity stays where it is when a reason triggers, e.g. an eliminated player.
+#[derive(Component, Default)]
+pub struct RespawnDisabled {
+    /// `RespawnEvent` is sent only once for a disabled entity
+    triggered: bool,
+}
+
+/// Sent whenever a respawn reason triggers, even if the respawn is disabled.
+#[derive(Debug, Event)]
+pub struct RespawnEvent {
+    pub entity: Entity,
+    pub reason: DespawnReason,
+}
+
 impl Respawn {
     pub fn new<T: IntoDespawnTypeVec>(
         reason: T,
@@ -38,6 +55,7 @@ impl Respawn {
         Self {
             reason: reason.into_despawn_type_vec(),
             spawn_point,
+            start_point: spawn_point,
             untuched_on_spawn,
         }
     }
@@ -46,6 +64,7 @@ impl Respawn {
         Self {
             reason: vec![],
             spawn_point,
+            start_point: spawn_point,
             untuched_on_spawn: UntouchedTimerValue::None,
         }
     }
@@ -53,6 +72,16 @@ impl Respawn {
     pub fn insert_reason(&mut self, reason: DespawnReason) {
         self.reason.push(reason);
     }
+
+    /// Where the entity comes back to from now on, e.g. a checkpoint.
+    pub fn set_spawn_point(&mut self, spawn_point: Vec3) {
+        self.spawn_point = spawn_point;
+    }
+
+    /// Forgets the checkpoints, the entity comes back to where it was first spawned.
+    pub fn reset_spawn_point(&mut self) {
+        self.spawn_point = self.start_point;
+    }
 }
 
 #[derive(Debug)]
@@ -70,7 +99,8 @@ pub struct ComponentPlugins;
 
 impl Plugin for ComponentPlugins {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreUpdate, (respawn, despawn))
+        app.add_event::<RespawnEvent>()
+            .add_systems(PreUpdate, (respawn, despawn))
             .add_systems(Update, timer_tick_system);
     }
 }
@@ -86,7 +116,7 @@ fn timer_tick_system(
                 .entity(entity)
                 .insert(CollisionLayers::new(
                     [MyLayers::Default],
-                    [MyLayers::Default, MyLayers::ActorNoclip],
+                    [MyLayers::Default, MyLayers::ActorNoclip, MyLayers::Sensor],
                 ))
                 .remove::<UntouchedTimer>();
         }
@@ -95,16 +125,35 @@ fn timer_tick_system(
 
 fn respawn(
     mut commands: Commands,
-    mut respawn_query: Query<(&mut Respawn, &mut Transform, &GlobalTransform, Entity)>,
+    mut respawn_query: Query<(
+        &mut Respawn,
+        &mut Transform,
+        &GlobalTransform,
+        Entity,
+        Option<&mut RespawnDisabled>,
+    )>,
     mut velocity_query: Query<(&mut LinearVelocity, &mut AngularVelocity), With<Respawn>>,
+    mut respawn_event: EventWriter<RespawnEvent>,
 ) {
+    #[allow(clippy::too_many_arguments)]
     fn respawn_act(
         commands: &mut Commands,
         respawn: &mut Respawn,
         transform: &mut Transform,
         entity: Entity,
         velocity_query: &mut Query<(&mut LinearVelocity, &mut AngularVelocity), With<Respawn>>,
+        reason: DespawnReason,
+        disabled: Option<&mut RespawnDisabled>,
+        respawn_event: &mut EventWriter<RespawnEvent>,
     ) {
+        if let Some(disabled) = disabled {
+            if !disabled.triggered {
+                disabled.triggered = true;
+                respawn_event.send(RespawnEvent { entity, reason });
+            }
+            return;
+        }
+        respawn_event.send(RespawnEvent { entity, reason });
         info!("Respawn entity: {:?}", entity);
         if let UntouchedTimerValue::Timer(val) = respawn.untuched_on_spawn {
             commands
@@ -115,7 +164,7 @@ fn respawn(
                 )))
                 .insert(CollisionLayers::new(
                     [MyLayers::ActorNoclip],
-                    [MyLayers::Default],
+                    [MyLayers::Default, MyLayers::Sensor],
                 ));
         }
         transform.translation = respawn.spawn_point;
@@ -125,20 +174,23 @@ fn respawn(
         }
     }
 
-    for (mut respawn, mut transform, global_transform, entity) in respawn_query.iter_mut() {
+    for (mut respawn, mut transform, global_transform, entity, mut disabled) in
+        respawn_query.iter_mut()
+    {
         for reason in respawn.reason.clone() {
             match reason {
-                DespawnReason::Forced => {
+                DespawnReason::Forced | DespawnReason::Killed => {
                     respawn_act(
                         &mut commands,
                         &mut respawn,
                         &mut transform,
                         entity,
                         &mut velocity_query,
+                        reason,
+                        disabled.as_deref_mut(),
+                        &mut respawn_event,
                     );
-                    respawn
-                        .reason
-                        .retain(|reason| reason != &DespawnReason::Forced);
+                    respawn.reason.retain(|other| other != &reason);
                 }
                 DespawnReason::Less(val, axis) => match axis {
                     AxisName::X => {
@@ -149,6 +201,9 @@ fn respawn(
                                 &mut transform,
                                 entity,
                                 &mut velocity_query,
+                                reason,
+                                disabled.as_deref_mut(),
+                                &mut respawn_event,
                             );
                         }
                     }
@@ -160,6 +215,9 @@ fn respawn(
                                 &mut transform,
                                 entity,
                                 &mut velocity_query,
+                                reason,
+                                disabled.as_deref_mut(),
+                                &mut respawn_event,
                             );
                         }
                     }
@@ -171,6 +229,9 @@ fn respawn(
                                 &mut transform,
                                 entity,
                                 &mut velocity_query,
+                                reason,
+                                disabled.as_deref_mut(),
+                                &mut respawn_event,
                             );
                         }
                     }
@@ -184,6 +245,9 @@ fn respawn(
                                 &mut transform,
                                 entity,
                                 &mut velocity_query,
+                                reason,
+                                disabled.as_deref_mut(),
+                                &mut respawn_event,
                             );
                         }
                     }
@@ -195,6 +259,9 @@ fn respawn(
                                 &mut transform,
                                 entity,
                                 &mut velocity_query,
+                                reason,
+                                disabled.as_deref_mut(),
+                                &mut respawn_event,
                             );
                         }
                     }
@@ -206,6 +273,9 @@ fn respawn(
                                 &mut transform,
                                 entity,
                                 &mut velocity_query,
+                                reason,
+                                disabled.as_deref_mut(),
+                                &mut respawn_event,
                             );
                         }
                     }
@@ -2,6 +2,8 @@
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DespawnReason {
     Forced,
+    /// Entered a kill zone, counted like a fall unlike `Forced`
+    Killed,
     More(f32, AxisName),
     Less(f32, AxisName),
 }
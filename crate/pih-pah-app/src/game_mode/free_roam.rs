@@ -0,0 +1,16 @@
+use bevy::prelude::*;
+
+use super::{GameModeRules, GameModeState};
+
+pub struct FreeRoamPlugins;
+
+impl Plugin for FreeRoamPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameModeState::FreeRoam), load);
+    }
+}
+
+/// No rounds, no score, respawn forever.
+fn load(mut commands: Commands) {
+    commands.insert_resource(GameModeRules::default());
+}
@@ -0,0 +1,86 @@
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{FreeRoamPlugins, KingOfTheHillPlugins, KnockoutPlugins, RoundPlugins};
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States, Serialize, Deserialize)]
+pub enum GameModeState {
+    #[default]
+    FreeRoam = 0,
+    Knockout = 1,
+    KingOfTheHill = 2,
+}
+
+impl Display for GameModeState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameModeState::FreeRoam => write!(f, "FreeRoam"),
+            GameModeState::Knockout => write!(f, "Knockout"),
+            GameModeState::KingOfTheHill => write!(f, "KingOfTheHill"),
+        }
+    }
+}
+
+impl GameModeState {
+    pub fn all() -> [GameModeState; 3] {
+        [
+            GameModeState::FreeRoam,
+            GameModeState::Knockout,
+            GameModeState::KingOfTheHill,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RespawnPolicy {
+    /// Characters return to their spawn point right away
+    Instant,
+    /// Characters stay out until the next round
+    Never,
+    /// Every respawn costs a life, characters are out when none are left
+    Lives(u32),
+}
+
+/// Rules of the active game mode, inserted by the mode plugin on enter.
+///
+/// Durations are in seconds, `None` phases are skipped (warmup) or endless (round).
+#[derive(Debug, Clone, Resource)]
+pub struct GameModeRules {
+    pub warmup: Option<f32>,
+    pub round: Option<f32>,
+    pub round_end: f32,
+    pub intermission: f32,
+    /// First player or team to reach it wins the round
+    pub target_score: Option<i32>,
+    pub respawn_policy: RespawnPolicy,
+}
+
+impl Default for GameModeRules {
+    fn default() -> Self {
+        Self {
+            warmup: None,
+            round: None,
+            round_end: 5.,
+            intermission: 5.,
+            target_score: None,
+            respawn_policy: RespawnPolicy::Instant,
+        }
+    }
+}
+
+pub struct GameModePlugins;
+
+impl Plugin for GameModePlugins {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameModeState>()
+            .init_resource::<GameModeRules>()
+            .add_plugins((
+                RoundPlugins,
+                FreeRoamPlugins,
+                KnockoutPlugins,
+                KingOfTheHillPlugins,
+            ));
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::Collisions;
+use serde::{Deserialize, Serialize};
+
+use crate::lobby::{Character, Lobby, LobbyState, PlayerId};
+use crate::world::{sensor_from_mesh, SceneTagAppExt, SceneTagContext, SceneTagErrorKind};
+
+use super::{
+    Eliminated, GameModeRules, GameModeState, RespawnPolicy, RoundPhase, ScoreEvent, Winner,
+};
+
+pub const CAPTURE_TARGET_SCORE: i32 = 60;
+/// Points are awarded once per tick to every player in an uncontested zone
+pub const CAPTURE_TICK_SECONDS: f32 = 1.;
+const ZONE_NEUTRAL_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.4);
+
+/// Sensor zone tagged `[z:<id>]` in the province scene.
+#[derive(Debug, Component)]
+pub struct CaptureZone(pub String);
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZoneControl {
+    pub owner: Option<Winner>,
+    /// Occupied by more than one player or team
+    pub contested: bool,
+}
+
+/// Control of every zone by its id, replicated to clients.
+#[derive(Debug, Default, Clone, Resource, Serialize, Deserialize)]
+pub struct CaptureZones(pub HashMap<String, ZoneControl>);
+
+#[derive(Debug, Resource)]
+struct CaptureTimer(Timer);
+
+impl Default for CaptureTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            CAPTURE_TICK_SECONDS,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+/// One material per zone color, so repainting a zone adds no asset.
+#[derive(Debug, Default, Resource)]
+struct ZoneMaterials(HashMap<u32, Handle<StandardMaterial>>);
+
+pub struct KingOfTheHillPlugins;
+
+impl Plugin for KingOfTheHillPlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CaptureZones>()
+            .init_resource::<CaptureTimer>()
+            .init_resource::<ZoneMaterials>()
+            .register_shared_scene_tag("z", zone_tag)
+            .register_scene_tag("z", zone_sensor_tag)
+            .add_systems(OnEnter(GameModeState::KingOfTheHill), load)
+            .add_systems(OnEnter(RoundPhase::Warmup), reset_zones)
+            .add_systems(
+                Update,
+                capture_zones.run_if(
+                    in_state(GameModeState::KingOfTheHill)
+                        .and_then(in_state(RoundPhase::Playing))
+                        .and_then(not(in_state(LobbyState::None)))
+                        .and_then(not(in_state(LobbyState::Client))),
+                ),
+            )
+            .add_systems(Update, paint_zones.run_if(not(in_state(LobbyState::None))));
+    }
+}
+
+/// `z:<id>`, names the capture zone.
+fn zone_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    commands
+        .entity(tag.entity)
+        .insert(CaptureZone(tag.text()?.to_string()));
+    Ok(())
+}
+
+/// Sensor of a `z` tagged node, occupants are only counted by the host.
+fn zone_sensor_tag(
+    commands: &mut Commands,
+    tag: &SceneTagContext,
+) -> Result<(), SceneTagErrorKind> {
+    commands.entity(tag.entity).insert(sensor_from_mesh(tag)?);
+    Ok(())
+}
+
+fn load(mut commands: Commands) {
+    commands.insert_resource(GameModeRules {
+        warmup: Some(5.),
+        round: Some(300.),
+        round_end: 5.,
+        intermission: 5.,
+        target_score: Some(CAPTURE_TARGET_SCORE),
+        respawn_policy: RespawnPolicy::Instant,
+    });
+    commands.insert_resource(CaptureZones::default());
+    commands.insert_resource(CaptureTimer::default());
+}
+
+fn reset_zones(mut zones: ResMut<CaptureZones>, mut capture_timer: ResMut<CaptureTimer>) {
+    if !zones.0.is_empty() {
+        zones.0.clear();
+    }
+    capture_timer.0.reset();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn capture_zones(
+    time: Res<Time>,
+    collisions: Res<Collisions>,
+    lobby: Option<Res<Lobby>>,
+    mut zones: ResMut<CaptureZones>,
+    mut capture_timer: ResMut<CaptureTimer>,
+    mut score_event: EventWriter<ScoreEvent>,
+    zone_query: Query<(Entity, &CaptureZone)>,
+    character_query: Query<&Character, Without<Eliminated>>,
+) {
+    let tick = capture_timer.0.tick(time.delta()).just_finished();
+
+    // teammates hold a zone together, everyone else holds it alone
+    let side = |player_id: PlayerId| {
+        lobby
+            .as_ref()
+            .and_then(|lobby| lobby.players.get(&player_id))
+            .and_then(|player_data| player_data.team)
+            .map_or(Winner::Player(player_id), Winner::Team)
+    };
+
+    for (entity, zone) in zone_query.iter() {
+        let occupants: Vec<PlayerId> = collisions
+            .collisions_with_entity(entity)
+            .filter_map(|contacts| {
+                let other = if contacts.entity1 == entity {
+                    contacts.entity2
+                } else {
+                    contacts.entity1
+                };
+                character_query
+                    .get(other)
+                    .ok()
+                    .map(|character| character.id)
+            })
+            .collect();
+
+        let mut sides = occupants.iter().map(|player_id| side(*player_id));
+        let holder = sides.next();
+        let contested = holder.is_some_and(|holder| sides.any(|side| side != holder));
+
+        let current = zones.0.get(&zone.0).cloned().unwrap_or_default();
+        let control = ZoneControl {
+            owner: if contested {
+                current.owner
+            } else {
+                holder.or(current.owner)
+            },
+            contested,
+        };
+        if control != current {
+            zones.0.insert(zone.0.clone(), control);
+        }
+
+        if tick && !contested {
+            for player_id in occupants {
+                score_event.send(ScoreEvent {
+                    player_id,
+                    points: 1,
+                });
+            }
+        }
+    }
+}
+
+/// Tints every zone with its owner's color.
+fn paint_zones(
+    zones: Res<CaptureZones>,
+    lobby: Option<Res<Lobby>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut zone_materials: ResMut<ZoneMaterials>,
+    mut zone_query: Query<(Ref<CaptureZone>, &mut Handle<StandardMaterial>)>,
+) {
+    for (zone, mut material) in zone_query.iter_mut() {
+        if !zones.is_changed() && !zone.is_added() {
+            continue;
+        }
+        let control = zones.0.get(&zone.0).cloned().unwrap_or_default();
+        let base_color = match control.owner {
+            Some(Winner::Team(team)) if !control.contested => team.color().with_a(0.4),
+            Some(Winner::Player(player_id)) if !control.contested => lobby
+                .as_ref()
+                .and_then(|lobby| lobby.players.get(&player_id))
+                .map_or(Color::WHITE, |player_data| player_data.character_color())
+                .with_a(0.4),
+            _ => ZONE_NEUTRAL_COLOR,
+        };
+        let handle = zone_materials
+            .0
+            .entry(base_color.as_rgba_u32())
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color,
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true,
+                    ..default()
+                })
+            });
+        if *material != *handle {
+            *material = handle.clone();
+        }
+    }
+}
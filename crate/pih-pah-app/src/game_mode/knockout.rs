@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::Collisions;
+
+use crate::character::KnockbackEvent;
+use crate::component::{DespawnReason, RespawnEvent};
+use crate::lobby::{Character, Lobby, LobbyState, PlayerId};
+
+use super::{
+    Eliminated, GameModeRules, GameModeState, RespawnPolicy, Round, RoundPhase, RoundWonEvent,
+    ScoreEvent, Winner,
+};
+
+pub const KNOCKOUT_LIVES: u32 = 3;
+/// A fall is credited to the last player who touched the victim within this time
+pub const KNOCKOUT_CREDIT_SECONDS: f32 = 5.;
+
+/// Last player who touched the character, for knockout credit.
+#[derive(Debug, Component)]
+pub struct LastTouchedBy {
+    pub player_id: PlayerId,
+    pub at: f32,
+}
+
+pub struct KnockoutPlugins;
+
+impl Plugin for KnockoutPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameModeState::Knockout), load)
+            .add_systems(
+                Update,
+                (
+                    track_touches,
+                    track_knockbacks,
+                    credit_knockouts,
+                    last_cube_standing,
+                )
+                    .chain()
+                    .run_if(
+                        in_state(GameModeState::Knockout)
+                            .and_then(in_state(RoundPhase::Playing))
+                            .and_then(not(in_state(LobbyState::None)))
+                            .and_then(not(in_state(LobbyState::Client))),
+                    ),
+            );
+    }
+}
+
+fn load(mut commands: Commands) {
+    commands.insert_resource(GameModeRules {
+        warmup: Some(5.),
+        round: Some(300.),
+        round_end: 5.,
+        intermission: 5.,
+        target_score: None,
+        respawn_policy: RespawnPolicy::Lives(KNOCKOUT_LIVES),
+    });
+}
+
+fn track_touches(
+    mut commands: Commands,
+    time: Res<Time>,
+    collisions: Res<Collisions>,
+    character_query: Query<(Entity, &Character)>,
+) {
+    for (entity, _) in character_query.iter() {
+        for contacts in collisions.collisions_with_entity(entity) {
+            let other = if contacts.entity1 == entity {
+                contacts.entity2
+            } else {
+                contacts.entity1
+            };
+            if let Ok((_, other_character)) = character_query.get(other) {
+                commands.entity(entity).insert(LastTouchedBy {
+                    player_id: other_character.id,
+                    at: time.elapsed_seconds(),
+                });
+            }
+        }
+    }
+}
+
+/// Shoves and ground pounds credit the attacker even without a contact.
+fn track_knockbacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut knockback_event: EventReader<KnockbackEvent>,
+) {
+    for KnockbackEvent { attacker, victim } in knockback_event.read() {
+        if let Some(mut entity_commands) = commands.get_entity(*victim) {
+            entity_commands.insert(LastTouchedBy {
+                player_id: *attacker,
+                at: time.elapsed_seconds(),
+            });
+        }
+    }
+}
+
+fn credit_knockouts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut respawn_event: EventReader<RespawnEvent>,
+    mut score_event: EventWriter<ScoreEvent>,
+    touched_query: Query<(&Character, &LastTouchedBy)>,
+) {
+    for RespawnEvent { entity, reason } in respawn_event.read() {
+        if *reason == DespawnReason::Forced {
+            continue;
+        }
+        let Ok((character, last_touched_by)) = touched_query.get(*entity) else {
+            continue;
+        };
+        if last_touched_by.player_id != character.id
+            && time.elapsed_seconds() - last_touched_by.at <= KNOCKOUT_CREDIT_SECONDS
+        {
+            score_event.send(ScoreEvent {
+                player_id: last_touched_by.player_id,
+                points: 1,
+            });
+        }
+        commands.entity(*entity).remove::<LastTouchedBy>();
+    }
+}
+
+fn last_cube_standing(
+    round: Res<Round>,
+    lobby: Option<Res<Lobby>>,
+    character_query: Query<&Character, Without<Eliminated>>,
+    all_character_query: Query<(), With<Character>>,
+    mut round_won_event: EventWriter<RoundWonEvent>,
+) {
+    // nobody to knock out alone
+    if all_character_query.iter().count() < 2 {
+        return;
+    }
+
+    let standing: Vec<PlayerId> = character_query
+        .iter()
+        .map(|character| character.id)
+        .filter(|player_id| !round.eliminated.contains(player_id))
+        .collect();
+
+    let team = |player_id: &PlayerId| {
+        lobby
+            .as_ref()
+            .and_then(|lobby| lobby.players.get(player_id))
+            .and_then(|player_data| player_data.team)
+    };
+
+    match standing.as_slice() {
+        [] => round_won_event.send(RoundWonEvent(None)),
+        [player_id] => round_won_event.send(RoundWonEvent(Some(Winner::Player(*player_id)))),
+        [first, rest @ ..] => {
+            // last team standing
+            if let Some(first_team) = team(first) {
+                if rest
+                    .iter()
+                    .all(|player_id| team(player_id) == Some(first_team))
+                {
+                    round_won_event.send(RoundWonEvent(Some(Winner::Team(first_team))));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+#![allow(clippy::module_inception)]
+
+mod free_roam;
+mod game_mode;
+mod king_of_the_hill;
+mod knockout;
+mod round;
+
+pub use free_roam::*;
+pub use game_mode::*;
+pub use king_of_the_hill::*;
+pub use knockout::*;
+pub use round::*;
@@ -0,0 +1,407 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::RigidBody;
+use serde::{Deserialize, Serialize};
+
+use crate::character::{FreeCamera, TiedCamera};
+use crate::component::{DespawnReason, Respawn, RespawnDisabled, RespawnEvent};
+use crate::lobby::room::{Room, RoomState};
+use crate::lobby::team::TeamId;
+use crate::lobby::{Character, Lobby, LobbyState, PlayerId};
+use crate::world::CheckpointProgress;
+
+use super::{GameModeRules, GameModeState, RespawnPolicy};
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States, Serialize, Deserialize)]
+pub enum RoundPhase {
+    #[default]
+    None = 0,
+    Warmup = 1,
+    Playing = 2,
+    RoundEnd = 3,
+    Intermission = 4,
+}
+
+impl Display for RoundPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoundPhase::None => write!(f, "None"),
+            RoundPhase::Warmup => write!(f, "Warmup"),
+            RoundPhase::Playing => write!(f, "Playing"),
+            RoundPhase::RoundEnd => write!(f, "RoundEnd"),
+            RoundPhase::Intermission => write!(f, "Intermission"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Winner {
+    Player(PlayerId),
+    Team(TeamId),
+}
+
+#[derive(Debug, Default, Clone, Resource, Serialize, Deserialize)]
+pub struct Round {
+    pub number: u32,
+    pub scores: HashMap<PlayerId, i32>,
+    /// `None` is a draw or a round still in progress
+    pub winner: Option<Winner>,
+    /// Lives left with `RespawnPolicy::Lives`
+    pub lives: HashMap<PlayerId, u32>,
+    /// Players out until the next round
+    pub eliminated: HashSet<PlayerId>,
+}
+
+impl Round {
+    pub fn score(&self, player_id: PlayerId) -> i32 {
+        self.scores.get(&player_id).copied().unwrap_or_default()
+    }
+
+    pub fn team_score(&self, lobby: &Lobby, team: TeamId) -> i32 {
+        lobby
+            .players
+            .iter()
+            .filter(|(_, player_data)| player_data.team == Some(team))
+            .map(|(player_id, _)| self.score(*player_id))
+            .sum()
+    }
+
+    /// Player or team with the highest score, `None` on a draw.
+    pub fn leader(&self, lobby: Option<&Lobby>) -> Option<(Winner, i32)> {
+        let team_scores: Vec<(Winner, i32)> = lobby
+            .map(|lobby| {
+                let mut teams: Vec<TeamId> = lobby
+                    .players
+                    .values()
+                    .filter_map(|player_data| player_data.team)
+                    .collect();
+                teams.sort();
+                teams.dedup();
+                teams
+                    .into_iter()
+                    .map(|team| (Winner::Team(team), self.team_score(lobby, team)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let scores = if team_scores.is_empty() {
+            self.scores
+                .iter()
+                .map(|(player_id, score)| (Winner::Player(*player_id), *score))
+                .collect()
+        } else {
+            team_scores
+        };
+
+        let best = scores.iter().map(|(_, score)| *score).max()?;
+        let mut leaders = scores.into_iter().filter(|(_, score)| *score == best);
+        match (leaders.next(), leaders.next()) {
+            (Some(leader), None) => Some(leader),
+            _ => None,
+        }
+    }
+}
+
+/// Character of an eliminated player, hidden and frozen until the next round.
+#[derive(Debug, Component)]
+pub struct Eliminated;
+
+/// Time left in the current phase, `None` for an endless phase.
+#[derive(Debug, Default, Resource)]
+pub struct RoundTimer(pub Option<Timer>);
+
+impl RoundTimer {
+    pub fn from_seconds(seconds: Option<f32>) -> Self {
+        Self(seconds.map(|seconds| Timer::from_seconds(seconds, TimerMode::Once)))
+    }
+
+    pub fn remaining_secs(&self) -> Option<f32> {
+        self.0.as_ref().map(|timer| timer.remaining_secs())
+    }
+}
+
+/// Game mode awards points to a player, counted only while playing.
+#[derive(Debug, Event)]
+pub struct ScoreEvent {
+    pub player_id: PlayerId,
+    pub points: i32,
+}
+
+/// Game mode decided the round by its own win condition, `None` is a draw.
+#[derive(Debug, Event)]
+pub struct RoundWonEvent(pub Option<Winner>);
+
+/// Jump to a phase, e.g. `Warmup` to (re)start the round cycle.
+#[derive(Debug, Event)]
+pub struct ChangeRoundPhase(pub RoundPhase);
+
+pub struct RoundPlugins;
+
+impl Plugin for RoundPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_state::<RoundPhase>()
+            .init_resource::<Round>()
+            .init_resource::<RoundTimer>()
+            .add_event::<ScoreEvent>()
+            .add_event::<RoundWonEvent>()
+            .add_event::<ChangeRoundPhase>()
+            .add_systems(
+                OnEnter(RoomState::Playing),
+                start_match.run_if(in_state(LobbyState::Host)),
+            )
+            .add_systems(OnEnter(LobbyState::Single), start_match)
+            .add_systems(OnEnter(LobbyState::None), stop_match)
+            .add_systems(
+                Update,
+                tick_round_timer.run_if(not(in_state(RoundPhase::None))),
+            )
+            .add_systems(
+                Update,
+                (restart_on_rules_change, advance_round).chain().run_if(
+                    not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
+                ),
+            )
+            .add_systems(
+                Update,
+                handle_falls.run_if(
+                    in_state(RoundPhase::Playing)
+                        .and_then(not(in_state(LobbyState::None)))
+                        .and_then(not(in_state(LobbyState::Client))),
+                ),
+            )
+            .add_systems(
+                Update,
+                spectate_when_eliminated.run_if(not(in_state(RoundPhase::None))),
+            )
+            .add_systems(
+                Update,
+                hide_eliminated_shells
+                    .run_if(in_state(LobbyState::Client).and_then(resource_exists::<Lobby>())),
+            );
+    }
+}
+
+fn start_match(mut change_round_phase: EventWriter<ChangeRoundPhase>) {
+    change_round_phase.send(ChangeRoundPhase(RoundPhase::Warmup));
+}
+
+fn stop_match(
+    mut round: ResMut<Round>,
+    mut round_timer: ResMut<RoundTimer>,
+    mut next_state_round_phase: ResMut<NextState<RoundPhase>>,
+    mut next_state_game_mode: ResMut<NextState<GameModeState>>,
+) {
+    *round = Round::default();
+    *round_timer = RoundTimer::default();
+    next_state_round_phase.set(RoundPhase::None);
+    next_state_game_mode.set(GameModeState::default());
+}
+
+fn tick_round_timer(time: Res<Time>, mut round_timer: ResMut<RoundTimer>) {
+    if let Some(timer) = round_timer.0.as_mut() {
+        timer.tick(time.delta());
+    }
+}
+
+fn restart_on_rules_change(
+    rules: Res<GameModeRules>,
+    round_phase: Res<State<RoundPhase>>,
+    mut change_round_phase: EventWriter<ChangeRoundPhase>,
+) {
+    if rules.is_changed() && *round_phase.get() != RoundPhase::None {
+        change_round_phase.send(ChangeRoundPhase(RoundPhase::Warmup));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn advance_round(
+    mut commands: Commands,
+    mut change_round_phase: EventReader<ChangeRoundPhase>,
+    mut score_event: EventReader<ScoreEvent>,
+    mut round_won_event: EventReader<RoundWonEvent>,
+    rules: Res<GameModeRules>,
+    lobby: Option<Res<Lobby>>,
+    mut round: ResMut<Round>,
+    mut round_timer: ResMut<RoundTimer>,
+    round_phase: Res<State<RoundPhase>>,
+    mut next_state_round_phase: ResMut<NextState<RoundPhase>>,
+    mut character_query: Query<(Entity, &Character, &mut Respawn)>,
+) {
+    let current = *round_phase.get();
+    let mut next = change_round_phase.read().last().map(|phase| phase.0);
+
+    if current == RoundPhase::Playing {
+        for ScoreEvent { player_id, points } in score_event.read() {
+            *round.scores.entry(*player_id).or_default() += points;
+        }
+        if let Some(target_score) = rules.target_score {
+            if let Some((winner, score)) = round.leader(lobby.as_deref()) {
+                if score >= target_score {
+                    round.winner = Some(winner);
+                    next = next.or(Some(RoundPhase::RoundEnd));
+                }
+            }
+        }
+        for RoundWonEvent(winner) in round_won_event.read() {
+            round.winner = *winner;
+            next = next.or(Some(RoundPhase::RoundEnd));
+        }
+    } else {
+        score_event.clear();
+        round_won_event.clear();
+    }
+
+    if next.is_none() && round_timer.0.as_ref().is_some_and(|timer| timer.finished()) {
+        next = match current {
+            RoundPhase::None => None,
+            RoundPhase::Warmup => Some(RoundPhase::Playing),
+            RoundPhase::Playing => {
+                round.winner = round.leader(lobby.as_deref()).map(|(winner, _)| winner);
+                Some(RoundPhase::RoundEnd)
+            }
+            RoundPhase::RoundEnd => Some(RoundPhase::Intermission),
+            RoundPhase::Intermission => Some(RoundPhase::Warmup),
+        };
+    }
+
+    let Some(mut next) = next else {
+        return;
+    };
+
+    if next == RoundPhase::Warmup {
+        round.number += 1;
+        round.scores.clear();
+        round.winner = None;
+        round.lives.clear();
+        round.eliminated.clear();
+        // everyone back to the start
+        for (entity, _, mut respawn) in character_query.iter_mut() {
+            commands
+                .entity(entity)
+                .remove::<(RespawnDisabled, Eliminated)>()
+                .insert((
+                    RigidBody::Dynamic,
+                    Visibility::Inherited,
+                    CheckpointProgress::default(),
+                ));
+            respawn.reset_spawn_point();
+            respawn.insert_reason(DespawnReason::Forced);
+        }
+        if rules.warmup.is_none() {
+            next = RoundPhase::Playing;
+        }
+    }
+
+    if next == RoundPhase::Playing {
+        for (entity, character, _) in character_query.iter() {
+            match rules.respawn_policy {
+                RespawnPolicy::Instant => {
+                    commands.entity(entity).remove::<RespawnDisabled>();
+                }
+                RespawnPolicy::Never => {
+                    commands.entity(entity).insert(RespawnDisabled::default());
+                }
+                RespawnPolicy::Lives(lives) => {
+                    round.lives.insert(character.id, lives);
+                    // the last fall is not respawned
+                    if lives <= 1 {
+                        commands.entity(entity).insert(RespawnDisabled::default());
+                    }
+                }
+            }
+        }
+    }
+
+    *round_timer = RoundTimer::from_seconds(match next {
+        RoundPhase::None => None,
+        RoundPhase::Warmup => rules.warmup,
+        RoundPhase::Playing => rules.round,
+        RoundPhase::RoundEnd => Some(rules.round_end),
+        RoundPhase::Intermission => Some(rules.intermission),
+    });
+    next_state_round_phase.set(next);
+}
+
+fn handle_falls(
+    mut commands: Commands,
+    mut respawn_event: EventReader<RespawnEvent>,
+    rules: Res<GameModeRules>,
+    mut round: ResMut<Round>,
+    character_query: Query<&Character, Without<Eliminated>>,
+) {
+    for RespawnEvent { entity, reason } in respawn_event.read() {
+        if *reason == DespawnReason::Forced {
+            continue;
+        }
+        let Ok(character) = character_query.get(*entity) else {
+            continue;
+        };
+
+        let eliminated = match rules.respawn_policy {
+            RespawnPolicy::Instant => false,
+            RespawnPolicy::Never => true,
+            RespawnPolicy::Lives(_) => {
+                let Some(lives) = round.lives.get_mut(&character.id) else {
+                    continue;
+                };
+                *lives = lives.saturating_sub(1);
+                if *lives == 1 {
+                    commands.entity(*entity).insert(RespawnDisabled::default());
+                }
+                *lives == 0
+            }
+        };
+
+        if eliminated {
+            round.eliminated.insert(character.id);
+            commands
+                .entity(*entity)
+                .insert((Eliminated, RigidBody::Static, Visibility::Hidden));
+        }
+    }
+}
+
+/// Local player watches from a free camera once out of the round.
+fn spectate_when_eliminated(
+    mut commands: Commands,
+    round: Res<Round>,
+    room: Option<Res<Room>>,
+    tied_camera_query: Query<(Entity, Has<FreeCamera>), With<TiedCamera>>,
+) {
+    let me = room.and_then(|room| room.me).unwrap_or(PlayerId::Host);
+    let eliminated = round.eliminated.contains(&me);
+
+    for (entity, free_camera) in tied_camera_query.iter() {
+        if eliminated && !free_camera {
+            commands.entity(entity).insert(FreeCamera);
+        } else if !eliminated && free_camera {
+            commands.entity(entity).remove::<FreeCamera>();
+        }
+    }
+}
+
+/// Host hides eliminated characters itself, clients follow the replicated round.
+fn hide_eliminated_shells(
+    round: Res<Round>,
+    lobby: Res<Lobby>,
+    mut visibility_query: Query<&mut Visibility>,
+) {
+    for (player_id, player_data) in lobby.players.iter() {
+        let Some(mut visibility) = player_data
+            .entity
+            .and_then(|entity| visibility_query.get_mut(entity).ok())
+        else {
+            continue;
+        };
+        let expected = if round.eliminated.contains(player_id) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        if *visibility != expected {
+            *visibility = expected;
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod character;
 pub mod component;
+pub mod game_mode;
 pub mod load;
 pub mod lobby;
 pub mod province;
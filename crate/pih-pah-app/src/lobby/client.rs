@@ -1,15 +1,19 @@
 use std::net::UdpSocket;
 use std::time::SystemTime;
 
-use crate::character::{spawn_character_shell, spawn_tied_camera, TiedCamera};
+use crate::character::{spawn_character_shell, spawn_tied_camera, CharacterConfig, TiedCamera};
+use crate::game_mode::{CaptureZones, GameModeState, Round, RoundPhase, RoundTimer};
+use crate::lobby::room::{Room, RoomAction, RoomState};
 use crate::lobby::{LobbyState, PlayerId};
-use crate::province::ProvinceState;
-use crate::world::{LinkId, Me};
+use crate::province::{ProvincePhysics, ProvinceState};
+use crate::settings::Settings;
+use crate::world::{LinkId, Me, MoverClock, MoverTrigger, TargetState};
 use bevy::app::{App, Plugin, Update};
 use bevy::ecs::entity::Entity;
+use bevy::ecs::event::EventReader;
 use bevy::ecs::query::With;
-use bevy::ecs::schedule::{Condition, NextState, OnExit};
-use bevy::ecs::system::{Query, Res, ResMut, Resource};
+use bevy::ecs::schedule::{Condition, NextState, OnExit, State};
+use bevy::ecs::system::{Query, Res, ResMut, Resource, SystemParam};
 use bevy::hierarchy::DespawnRecursiveExt;
 use bevy::math::Vec3;
 use bevy::prelude::{in_state, Commands, IntoSystemConfigs, OnEnter};
@@ -23,10 +27,25 @@ use renet::{ClientId, ConnectionConfig, DefaultChannel, RenetClient};
 pub struct OwnId(Option<ClientId>);
 
 use super::{
-    ClientResource, Lobby, PlayerData, PlayerInput, ServerMessages, TransportDataResource,
-    Username, PROTOCOL_ID,
+    ClientMessages, ClientResource, ConnectData, Lobby, PlayerData, PlayerInput, ServerMessages,
+    TransportDataResource, PROTOCOL_ID,
 };
 
+/// Host-owned resources the client only mirrors.
+#[derive(SystemParam)]
+pub struct ReplicatedResources<'w> {
+    round: ResMut<'w, Round>,
+    round_timer: ResMut<'w, RoundTimer>,
+    zones: ResMut<'w, CaptureZones>,
+    character_config: ResMut<'w, CharacterConfig>,
+    physics: ResMut<'w, ProvincePhysics>,
+    mover_clock: ResMut<'w, MoverClock>,
+}
+
+/// Yaw of a `Facing` message, kept until the shell of the local character is spawned.
+#[derive(Debug, Default, Resource)]
+struct PendingFacing(Option<f32>);
+
 pub struct ClientLobbyPlugins;
 
 impl Plugin for ClientLobbyPlugins {
@@ -35,14 +54,28 @@ impl Plugin for ClientLobbyPlugins {
             .add_systems(OnEnter(LobbyState::Client), (setup, new_renet_client))
             .add_systems(
                 Update,
-                (client_send_input, client_sync_players)
+                (
+                    client_send_input,
+                    client_sync_players,
+                    apply_pending_facing.after(client_sync_players),
+                    handle_room_action,
+                )
                     .run_if(in_state(LobbyState::Client).and_then(bevy_renet::client_connected())),
             )
+            .add_systems(
+                Update,
+                spawn_character_shells
+                    .run_if(in_state(LobbyState::Client).and_then(in_state(RoomState::Playing))),
+            )
             .add_systems(OnExit(LobbyState::Client), teardown);
     }
 }
 
-pub fn new_renet_client(settings: Res<ClientResource>, mut commands: Commands) {
+pub fn new_renet_client(
+    settings: Res<ClientResource>,
+    user_settings: Res<Settings>,
+    mut commands: Commands,
+) {
     commands.insert_resource(RenetClient::new(ConnectionConfig::default()));
     let server_addr = settings.address.clone().unwrap().parse().unwrap();
     let socket = UdpSocket::bind("0.0.0.0:0").unwrap();
@@ -51,11 +84,14 @@ pub fn new_renet_client(settings: Res<ClientResource>, mut commands: Commands) {
         .unwrap();
     let client_id = current_time.as_millis() as u64;
 
-    let username_netcode =
-        match Username(settings.username.clone().unwrap().clone()).to_netcode_data() {
-            Ok(bytes) => Some(bytes),
-            Err(_) => None,
-        };
+    let connect_data = ConnectData {
+        username: settings.username.clone().unwrap(),
+        appearance: user_settings.appearance.clone(),
+    };
+    let username_netcode = match connect_data.to_netcode_data() {
+        Ok(bytes) => Some(bytes),
+        Err(_) => None,
+    };
 
     let authentication = ClientAuthentication::Unsecure {
         client_id,
@@ -74,7 +110,8 @@ pub fn client_send_input(
     mut client: ResMut<RenetClient>,
 ) {
     if let Ok(player_input) = player_input_query.get_single_mut() {
-        let input_message = bincode::serialize(&*player_input).unwrap();
+        let input_message =
+            bincode::serialize(&ClientMessages::Input(player_input.clone())).unwrap();
 
         client.send_message(DefaultChannel::ReliableOrdered, input_message);
     }
@@ -88,13 +125,16 @@ fn setup(mut commands: Commands) {
     // commands.spawn_tied_camera(entity);
     commands.init_resource::<Lobby>();
     commands.init_resource::<OwnId>();
+    commands.init_resource::<Room>();
     commands.init_resource::<TransportDataResource>();
+    commands.init_resource::<PendingFacing>();
 }
 
 fn teardown(
     mut commands: Commands,
     tied_camera_query: Query<Entity, With<TiedCamera>>,
     char_query: Query<Entity, With<PlayerInput>>,
+    mut next_state_room: ResMut<NextState<RoomState>>,
 ) {
     for entity in tied_camera_query.iter() {
         commands.entity(entity).despawn_recursive();
@@ -104,7 +144,10 @@ fn teardown(
     }
     commands.remove_resource::<Lobby>();
     commands.remove_resource::<OwnId>();
+    commands.remove_resource::<Room>();
     commands.remove_resource::<TransportDataResource>();
+    commands.remove_resource::<PendingFacing>();
+    next_state_room.set(RoomState::None);
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -114,62 +157,148 @@ pub fn client_sync_players(
     mut transport_data: ResMut<TransportDataResource>,
     mut lobby: ResMut<Lobby>,
     mut own_id: ResMut<OwnId>,
-    mut tied_camera_query: Query<&mut Transform, With<TiedCamera>>,
+    mut room: ResMut<Room>,
     mut next_state_province: ResMut<NextState<ProvinceState>>,
+    mut next_state_room: ResMut<NextState<RoomState>>,
+    mut next_state_game_mode: ResMut<NextState<GameModeState>>,
+    mut next_state_round_phase: ResMut<NextState<RoundPhase>>,
+    mut replicated: ReplicatedResources,
     lincked_obj_query: Query<(Entity, &LinkId)>,
+    mut trigger_query: Query<(&mut MoverTrigger, &LinkId)>,
+    mut target_query: Query<(&mut TargetState, &LinkId)>,
+    mut pending_facing: ResMut<PendingFacing>,
 ) {
     // player existence manager
     while let Some(message) = client.receive_message(DefaultChannel::ReliableOrdered) {
         let server_message = bincode::deserialize(&message).unwrap();
         match server_message {
-            ServerMessages::InitConnection { id, province_state } => {
-                next_state_province.set(province_state);
+            ServerMessages::InitConnection {
+                id,
+                province_state,
+                game_mode,
+                room_state,
+            } => {
+                // the province is loaded only when the match is already running
+                if room_state == RoomState::Playing {
+                    next_state_province.set(province_state);
+                    next_state_game_mode.set(game_mode);
+                }
+                next_state_room.set(room_state);
                 if own_id.0.is_some() {
                     panic!("Yeah, I knew it. The server only had to initialize me once. Redo it, you idiot.");
                 } else {
                     *own_id = OwnId(Some(id));
+                    room.me = Some(PlayerId::Client(id));
                 }
             }
             ServerMessages::ChangeProvince { province_state } => {
+                room.province_state = province_state.clone();
                 next_state_province.set(province_state);
             }
+            ServerMessages::ChangeGameMode { game_mode } => {
+                room.game_mode = game_mode;
+                next_state_game_mode.set(game_mode);
+            }
             ServerMessages::PlayerConnected {
                 id: player_id,
                 color,
                 username,
+                ready,
+                team,
+                appearance,
             } => {
-                let name = "noname";
-
-                let player_entity = commands.spawn_character_shell(color, Vec3::ZERO).id();
                 if let PlayerId::Client(id) = player_id {
                     if Some(id) == own_id.0 {
-                        commands.entity(player_entity).insert(Me);
-                        commands.spawn_tied_camera(player_entity);
-                        log::info!("{name} ({id}), welcome.");
+                        log::info!("{username} ({id}), welcome.");
                     } else {
-                        log::info!("Player {} ({}) connected.", name, id);
+                        log::info!("Player {} ({}) connected.", username, id);
                     }
                 } else {
-                    log::info!("Host {} ({:?}).", name, player_id);
+                    log::info!("Host {} ({:?}).", username, player_id);
                 }
 
+                // Character shell is spawned by `spawn_character_shells` once the match is running
                 lobby.players.insert(
                     player_id,
                     PlayerData {
-                        entity: player_entity,
+                        entity: None,
                         color,
                         username,
+                        ready,
+                        team,
+                        appearance,
                     },
                 );
             }
             ServerMessages::PlayerDisconnected { id } => {
-                let name = "noname";
-
-                log::info!("Player {} ({:?}) disconnected.", name, id);
                 if let Some(player_data) = lobby.players.remove(&id) {
-                    commands.entity(player_data.entity).despawn();
+                    log::info!("Player {} ({:?}) disconnected.", player_data.username, id);
+                    if let Some(entity) = player_data.entity {
+                        commands.entity(entity).despawn();
+                    }
                 }
             }
+            ServerMessages::RoomPlayerUpdate {
+                id,
+                color,
+                ready,
+                team,
+            } => {
+                if let Some(player_data) = lobby.players.get_mut(&id) {
+                    player_data.color = color;
+                    player_data.ready = ready;
+                    player_data.team = team;
+                }
+            }
+            ServerMessages::RoomTeams { team_count } => {
+                room.team_count = team_count;
+            }
+            ServerMessages::RoomProvince { province_state } => {
+                room.province_state = province_state;
+            }
+            ServerMessages::RoomGameMode { game_mode } => {
+                room.game_mode = game_mode;
+            }
+            ServerMessages::RoomCountdown { seconds } => {
+                room.start_countdown(seconds);
+                next_state_room.set(RoomState::Countdown);
+            }
+            ServerMessages::RoomCountdownCanceled => {
+                next_state_room.set(RoomState::Waiting);
+            }
+            ServerMessages::StartMatch {
+                province_state,
+                game_mode,
+            } => {
+                room.province_state = province_state.clone();
+                room.game_mode = game_mode;
+                next_state_province.set(province_state);
+                next_state_game_mode.set(game_mode);
+                next_state_room.set(RoomState::Playing);
+            }
+            ServerMessages::RoundUpdate {
+                phase,
+                round: server_round,
+                remaining,
+            } => {
+                *replicated.round = server_round;
+                *replicated.round_timer = RoundTimer::from_seconds(remaining);
+                next_state_round_phase.set(phase);
+            }
+            ServerMessages::ZoneUpdate {
+                zones: server_zones,
+            } => {
+                *replicated.zones = server_zones;
+            }
+            ServerMessages::ChangeCharacterConfig { config } => {
+                *replicated.character_config = config;
+            }
+            ServerMessages::ChangePhysics { physics } => {
+                *replicated.physics = physics;
+            }
+            ServerMessages::Facing { yaw } => {
+                pending_facing.0 = Some(yaw);
+            }
         }
     }
 
@@ -177,22 +306,27 @@ pub fn client_sync_players(
     while let Some(message) = client.receive_message(DefaultChannel::Unreliable) {
         transport_data.data = bincode::deserialize(&message).unwrap();
         for (player_id, data) in transport_data.data.players.iter() {
-            if let Some(player_data) = lobby.players.get(player_id) {
+            if let Some(player_entity) = lobby
+                .players
+                .get(player_id)
+                .and_then(|player_data| player_data.entity)
+            {
                 let transform = Transform {
                     translation: data.position,
                     rotation: data.rotation,
                     ..Default::default()
                 };
                 // TODO: why transform to default?
-                commands.entity(player_data.entity).insert(transform);
-                if let PlayerId::Client(id) = player_id {
-                    if Some(id) == own_id.0.as_ref() {
-                        if let Ok(mut camera_transform) = tied_camera_query.get_single_mut() {
-                            camera_transform.translation = transform.translation;
-                            camera_transform.rotation = data.tied_camera_rotation;
-                        }
-                    }
-                }
+                commands.entity(player_entity).insert((
+                    transform,
+                    data.view_direction,
+                    data.cooldowns,
+                    data.damage,
+                    data.ping,
+                    data.checkpoint,
+                    data.shots,
+                ));
+                // own tied camera is smoothed towards the shell by `tied_camera_follow`
             }
         }
 
@@ -208,5 +342,87 @@ pub fn client_sync_players(
                 }
             }
         }
+
+        replicated.mover_clock.sync(transport_data.data.clock);
+        for (mut trigger, link_id) in trigger_query.iter_mut() {
+            if let Some(host_trigger) = transport_data.data.triggers.get(link_id) {
+                if *trigger != *host_trigger {
+                    *trigger = *host_trigger;
+                }
+            }
+        }
+        for (mut state, link_id) in target_query.iter_mut() {
+            if let Some(host_state) = transport_data.data.targets.get(link_id) {
+                if *state != *host_state {
+                    *state = *host_state;
+                }
+            }
+        }
+    }
+}
+
+/// Turns the local character to the facing of its spawn point once its shell exists.
+fn apply_pending_facing(
+    mut pending_facing: ResMut<PendingFacing>,
+    mut me_query: Query<&mut PlayerInput, With<Me>>,
+) {
+    let Ok(mut player_input) = me_query.get_single_mut() else {
+        return;
+    };
+    if let Some(yaw) = pending_facing.0.take() {
+        player_input.yaw = yaw;
+    }
+}
+
+fn spawn_character_shells(mut commands: Commands, mut lobby: ResMut<Lobby>, room: Res<Room>) {
+    for (player_id, player_data) in lobby.players.iter_mut() {
+        if player_data.entity.is_some() {
+            continue;
+        }
+
+        let player_entity = commands
+            .spawn_character_shell(
+                player_data.character_color(),
+                player_data.appearance.clone(),
+                Vec3::ZERO,
+            )
+            .id();
+        if Some(*player_id) == room.me {
+            commands.entity(player_entity).insert(Me);
+            commands.spawn_tied_camera(player_entity);
+        }
+        player_data.entity = Some(player_entity);
+    }
+}
+
+fn handle_room_action(
+    mut room_action: EventReader<RoomAction>,
+    mut client: ResMut<RenetClient>,
+    lobby: Res<Lobby>,
+    room: Res<Room>,
+    room_state: Res<State<RoomState>>,
+) {
+    let Some(player_data) = room.me.and_then(|me| lobby.players.get(&me)) else {
+        return;
+    };
+
+    for action in room_action.read() {
+        // province, game mode, teams and start are host only
+        let (color, ready, team) = match action {
+            RoomAction::Ready(ready) => (player_data.color, *ready, player_data.team),
+            RoomAction::Color(color) => (*color, player_data.ready, player_data.team),
+            RoomAction::Team(team) => (player_data.color, player_data.ready, Some(*team)),
+            RoomAction::TeamCount(_)
+            | RoomAction::Province(_)
+            | RoomAction::GameMode(_)
+            | RoomAction::Start => continue,
+        };
+        if *room_state.get() == RoomState::Playing {
+            continue;
+        }
+
+        let message =
+            bincode::serialize(&ClientMessages::RoomUpdate { color, ready, team }).unwrap();
+        client.send_message(DefaultChannel::ReliableOrdered, message);
     }
 }
@@ -1,19 +1,30 @@
 use std::net::UdpSocket;
 use std::time::SystemTime;
 
-use crate::character::{spawn_character, spawn_tied_camera, TiedCamera};
+use crate::character::{
+    spawn_character, spawn_tied_camera, AbilityCooldowns, CharacterConfig, Damage, ShotStats,
+    TiedCamera,
+};
 use crate::component::{DespawnReason, Respawn};
-use crate::lobby::{LobbyState, PlayerData, PlayerId, ServerMessages, Username};
-use crate::province::{ProvinceState, SpawnPoint};
-use crate::world::{LinkId, Me};
+use crate::game_mode::{CaptureZones, GameModeState, Round, RoundPhase, RoundTimer};
+use crate::lobby::room::{Room, RoomAction, RoomState, ROOM_COUNTDOWN_SECONDS};
+use crate::lobby::team::{TeamId, MAX_TEAMS};
+use crate::lobby::{ClientMessages, ConnectData, LobbyState, PlayerData, PlayerId, ServerMessages};
+use crate::province::{ProvincePhysics, ProvinceState, Provinces, SpawnPoint};
+use crate::settings::Settings;
+use crate::world::{CheckpointProgress, LinkId, Me, Mover, MoverClock, MoverTrigger, TargetState};
 use bevy::app::{App, Plugin, Update};
 use bevy::ecs::entity::Entity;
 use bevy::ecs::event::{Event, EventReader};
-use bevy::ecs::query::With;
-use bevy::ecs::schedule::{OnExit, State};
+use bevy::ecs::query::{With, Without};
+use bevy::ecs::schedule::{NextState, OnExit, State};
 use bevy::ecs::system::{Query, Res, ResMut};
 use bevy::hierarchy::DespawnRecursiveExt;
-use bevy::prelude::{in_state, Color, Commands, IntoSystemConfigs, OnEnter};
+use bevy::math::Vec3;
+use bevy::prelude::{
+    default, in_state, resource_changed, state_changed, Color, Commands, Condition,
+    IntoSystemConfigs, OnEnter,
+};
 use bevy::transform::components::Transform;
 use bevy_renet::transport::NetcodeServerPlugin;
 use bevy_renet::RenetServerPlugin;
@@ -22,18 +33,22 @@ use renet::transport::{NetcodeServerTransport, ServerAuthentication, ServerConfi
 use renet::{ConnectionConfig, DefaultChannel, RenetServer, ServerEvent};
 
 use super::{
-    Character, HostResource, Lobby, ObjectTransportData, PlayerInput, PlayerTransportData,
+    Character, HostResource, Lobby, ObjectTransportData, Ping, PlayerInput, PlayerTransportData,
     PlayerViewDirection, TransportDataResource, PROTOCOL_ID,
 };
 
 #[derive(Debug, Event)]
 pub struct ChangeProvinceServerEvent(pub ProvinceState);
 
+#[derive(Debug, Event)]
+pub struct ChangeGameModeServerEvent(pub GameModeState);
+
 pub struct HostLobbyPlugins;
 
 impl Plugin for HostLobbyPlugins {
     fn build(&self, app: &mut App) {
         app.add_event::<ChangeProvinceServerEvent>()
+            .add_event::<ChangeGameModeServerEvent>()
             .add_plugins((RenetServerPlugin, NetcodeServerPlugin))
             .add_systems(OnEnter(LobbyState::Host), setup)
             .add_systems(
@@ -42,10 +57,48 @@ impl Plugin for HostLobbyPlugins {
                     update,
                     server_update_system,
                     send_change_province,
+                    send_change_game_mode,
                     server_sync_players,
+                    handle_room_action,
                 )
                     .run_if(in_state(LobbyState::Host)),
             )
+            .add_systems(
+                Update,
+                update_countdown
+                    .run_if(in_state(LobbyState::Host).and_then(in_state(RoomState::Countdown))),
+            )
+            .add_systems(
+                Update,
+                send_round_update.run_if(
+                    in_state(LobbyState::Host).and_then(
+                        state_changed::<RoundPhase>().or_else(resource_changed::<Round>()),
+                    ),
+                ),
+            )
+            .add_systems(
+                Update,
+                send_zone_update.run_if(
+                    in_state(LobbyState::Host).and_then(resource_changed::<CaptureZones>()),
+                ),
+            )
+            .add_systems(
+                Update,
+                send_character_config.run_if(
+                    in_state(LobbyState::Host).and_then(resource_changed::<CharacterConfig>()),
+                ),
+            )
+            .add_systems(
+                Update,
+                send_physics.run_if(
+                    in_state(LobbyState::Host).and_then(resource_changed::<ProvincePhysics>()),
+                ),
+            )
+            .add_systems(
+                Update,
+                spawn_characters
+                    .run_if(in_state(LobbyState::Host).and_then(in_state(RoomState::Playing))),
+            )
             .add_systems(OnExit(LobbyState::Host), teardown);
     }
 }
@@ -71,28 +124,37 @@ pub fn new_renet_server(addr: &str) -> (RenetServer, NetcodeServerTransport) {
     (server, transport)
 }
 
-fn setup(mut commands: Commands, host_resource: Res<HostResource>, spawn_point: Res<SpawnPoint>) {
+fn setup(
+    mut commands: Commands,
+    host_resource: Res<HostResource>,
+    settings: Res<Settings>,
+    provinces: Res<Provinces>,
+    mut next_state_room: ResMut<NextState<RoomState>>,
+) {
     commands.init_resource::<TransportDataResource>();
 
     let mut lobby = Lobby::default();
     lobby.players_seq += 1;
     let color = generate_player_color(lobby.players_seq as u32);
 
-    let player_entity = commands
-        .spawn_character(PlayerId::Host, color, spawn_point.random_point())
-        .insert(Me)
-        .id();
-    commands.spawn_tied_camera(player_entity);
-
     lobby.players.insert(
         PlayerId::Host,
         PlayerData {
-            entity: player_entity,
+            entity: None,
             color,
             username: host_resource.username.clone().unwrap(),
+            ready: false,
+            team: None,
+            appearance: settings.appearance.clone(),
         },
     );
     commands.insert_resource(lobby);
+    commands.insert_resource(Room {
+        me: Some(PlayerId::Host),
+        province_state: provinces.default_state(),
+        ..Default::default()
+    });
+    next_state_room.set(RoomState::Waiting);
 
     let (server, transport) = new_renet_server(host_resource.address.clone().unwrap().as_str());
     commands.insert_resource(server);
@@ -105,6 +167,7 @@ fn teardown(
     mut commands: Commands,
     tied_camera_query: Query<Entity, With<TiedCamera>>,
     char_query: Query<Entity, With<PlayerInput>>,
+    mut next_state_room: ResMut<NextState<RoomState>>,
 ) {
     for entity in tied_camera_query.iter() {
         commands.entity(entity).despawn_recursive();
@@ -113,7 +176,9 @@ fn teardown(
         commands.entity(entity).despawn_recursive();
     }
     commands.remove_resource::<Lobby>();
+    commands.remove_resource::<Room>();
     commands.remove_resource::<TransportDataResource>();
+    next_state_room.set(RoomState::None);
 }
 
 pub fn generate_player_color(player_number: u32) -> Color {
@@ -122,14 +187,23 @@ pub fn generate_player_color(player_number: u32) -> Color {
     Color::hsl(hue, 1.0, 0.5)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn server_update_system(
     mut server_events: EventReader<ServerEvent>,
     mut commands: Commands,
     mut lobby: ResMut<Lobby>,
     mut server: ResMut<RenetServer>,
     transport: Res<NetcodeServerTransport>,
-    spawn_point: Res<SpawnPoint>,
-    province_state: ResMut<State<ProvinceState>>,
+    room: Res<Room>,
+    room_state: Res<State<RoomState>>,
+    province_state: Res<State<ProvinceState>>,
+    game_mode_state: Res<State<GameModeState>>,
+    round: Res<Round>,
+    round_timer: Res<RoundTimer>,
+    round_phase: Res<State<RoundPhase>>,
+    zones: Res<CaptureZones>,
+    character_config: Res<CharacterConfig>,
+    physics: Res<ProvincePhysics>,
 ) {
     for event in server_events.read() {
         match event {
@@ -139,22 +213,61 @@ pub fn server_update_system(
                 // TODO remove
                 let message = bincode::serialize(&ServerMessages::InitConnection {
                     id: *client_id,
-                    province_state: *province_state.get(),
+                    province_state: province_state.get().clone(),
+                    game_mode: *game_mode_state.get(),
+                    room_state: *room_state.get(),
+                })
+                .unwrap();
+                server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
+
+                let message = bincode::serialize(&ServerMessages::RoomGameMode {
+                    game_mode: room.game_mode,
+                })
+                .unwrap();
+                server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
+
+                let message = bincode::serialize(&ServerMessages::RoundUpdate {
+                    phase: *round_phase.get(),
+                    round: round.clone(),
+                    remaining: round_timer.remaining_secs(),
+                })
+                .unwrap();
+                server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
+
+                let message = bincode::serialize(&ServerMessages::ZoneUpdate {
+                    zones: zones.clone(),
+                })
+                .unwrap();
+                server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
+
+                let message = bincode::serialize(&ServerMessages::ChangeCharacterConfig {
+                    config: character_config.clone(),
+                })
+                .unwrap();
+                server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
+
+                let message = bincode::serialize(&ServerMessages::ChangePhysics {
+                    physics: physics.clone(),
+                })
+                .unwrap();
+                server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
+
+                let message = bincode::serialize(&ServerMessages::RoomProvince {
+                    province_state: room.province_state.clone(),
+                })
+                .unwrap();
+                server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
+
+                let message = bincode::serialize(&ServerMessages::RoomTeams {
+                    team_count: room.team_count,
                 })
                 .unwrap();
                 server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
 
                 lobby.players_seq += 1;
                 let color = generate_player_color(lobby.players_seq as u32);
-
-                // Spawn player cube
-                let player_entity = commands
-                    .spawn_character(
-                        PlayerId::Client(*client_id),
-                        color,
-                        spawn_point.random_point(),
-                    )
-                    .id();
+                // auto-balance
+                let team = lobby.smallest_team(room.team_count);
 
                 // We could send an InitState with all the players id and positions for the multiplayer
                 // but this is easier to do.
@@ -163,24 +276,34 @@ pub fn server_update_system(
                         id: *player_id,
                         color: player_data.color,
                         username: player_data.username.clone(),
+                        ready: player_data.ready,
+                        team: player_data.team,
+                        appearance: player_data.appearance.clone(),
                     })
                     .unwrap();
                     server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
                 }
 
                 let data = transport.user_data(*client_id).unwrap();
-                let username = match Username::from_user_data(&data) {
-                    Ok(name) => name,
-                    Err(_) => "@corapted@".to_string(),
-                };
+                let ConnectData {
+                    username,
+                    appearance,
+                } = ConnectData::from_user_data(&data).unwrap_or_else(|_| ConnectData {
+                    username: "@corapted@".to_string(),
+                    ..Default::default()
+                });
                 // let username = "noname".to_string();
 
+                // Character is spawned by `spawn_characters` once the match is running
                 lobby.players.insert(
                     PlayerId::Client(*client_id),
                     PlayerData {
-                        entity: player_entity,
+                        entity: None,
                         color,
                         username: username.clone(),
+                        ready: false,
+                        team,
+                        appearance: appearance.clone(),
                     },
                 );
 
@@ -188,6 +311,9 @@ pub fn server_update_system(
                     id: PlayerId::Client(*client_id),
                     color,
                     username,
+                    ready: false,
+                    team,
+                    appearance,
                 })
                 .unwrap();
                 server.broadcast_message(DefaultChannel::ReliableOrdered, message);
@@ -195,7 +321,9 @@ pub fn server_update_system(
             ServerEvent::ClientDisconnected { client_id, reason } => {
                 log::info!("Player {} disconnected: {}", client_id, reason);
                 if let Some(player_data) = lobby.players.remove(&PlayerId::Client(*client_id)) {
-                    commands.entity(player_data.entity).despawn();
+                    if let Some(entity) = player_data.entity {
+                        commands.entity(entity).despawn();
+                    }
                 }
 
                 let message = bincode::serialize(&ServerMessages::PlayerDisconnected {
@@ -210,22 +338,246 @@ pub fn server_update_system(
     for client_id in server.clients_id().into_iter() {
         while let Some(message) = server.receive_message(client_id, DefaultChannel::ReliableOrdered)
         {
-            let player_input: PlayerInput = bincode::deserialize(&message).unwrap();
-            if let Some(player_data) = lobby.players.get(&PlayerId::Client(client_id)) {
-                commands.entity(player_data.entity).insert(player_input);
+            // a client of another build must not take the server down
+            let client_message: ClientMessages = match bincode::deserialize(&message) {
+                Ok(client_message) => client_message,
+                Err(err) => {
+                    log::error!("Invalid message from player {}: {}", client_id, err);
+                    server.disconnect(client_id);
+                    break;
+                }
+            };
+            let Some(player_data) = lobby.players.get_mut(&PlayerId::Client(client_id)) else {
+                continue;
+            };
+            match client_message {
+                ClientMessages::Input(player_input) => {
+                    if let Some(entity) = player_data.entity {
+                        commands.entity(entity).insert(player_input);
+                    }
+                }
+                ClientMessages::RoomUpdate { color, ready, team } => {
+                    player_data.color = color;
+                    player_data.ready = ready;
+                    if is_valid_team(team, room.team_count) {
+                        player_data.team = team;
+                    }
+                    broadcast_room_player(&mut server, &lobby, PlayerId::Client(client_id));
+                }
             }
         }
     }
 }
 
+pub fn handle_room_action(
+    mut room_action: EventReader<RoomAction>,
+    mut lobby: ResMut<Lobby>,
+    mut room: ResMut<Room>,
+    mut server: ResMut<RenetServer>,
+    provinces: Res<Provinces>,
+    room_state: Res<State<RoomState>>,
+    mut next_state_room: ResMut<NextState<RoomState>>,
+) {
+    for action in room_action.read() {
+        match action {
+            RoomAction::Ready(ready) => {
+                if let Some(player_data) = lobby.players.get_mut(&PlayerId::Host) {
+                    player_data.ready = *ready;
+                }
+                broadcast_room_player(&mut server, &lobby, PlayerId::Host);
+            }
+            RoomAction::Color(color) => {
+                if let Some(player_data) = lobby.players.get_mut(&PlayerId::Host) {
+                    player_data.color = *color;
+                }
+                broadcast_room_player(&mut server, &lobby, PlayerId::Host);
+            }
+            RoomAction::Team(team) => {
+                if !is_valid_team(Some(*team), room.team_count) {
+                    continue;
+                }
+                if let Some(player_data) = lobby.players.get_mut(&PlayerId::Host) {
+                    player_data.team = Some(*team);
+                }
+                broadcast_room_player(&mut server, &lobby, PlayerId::Host);
+            }
+            RoomAction::TeamCount(team_count) => {
+                room.team_count = (*team_count).min(MAX_TEAMS);
+                lobby.balance_teams(room.team_count);
+
+                let message = bincode::serialize(&ServerMessages::RoomTeams {
+                    team_count: room.team_count,
+                })
+                .unwrap();
+                server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+
+                let player_ids: Vec<PlayerId> = lobby.players.keys().copied().collect();
+                for player_id in player_ids {
+                    broadcast_room_player(&mut server, &lobby, player_id);
+                }
+            }
+            RoomAction::Province(province_state) => {
+                let Some(manifest) = provinces.get(province_state) else {
+                    continue;
+                };
+                room.province_state = province_state.clone();
+
+                let message = bincode::serialize(&ServerMessages::RoomProvince {
+                    province_state: province_state.clone(),
+                })
+                .unwrap();
+                server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+
+                // the manifest may not allow the picked game mode
+                if !manifest.allows(room.game_mode) {
+                    room.game_mode = manifest.game_modes()[0];
+
+                    let message = bincode::serialize(&ServerMessages::RoomGameMode {
+                        game_mode: room.game_mode,
+                    })
+                    .unwrap();
+                    server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+                }
+            }
+            RoomAction::GameMode(game_mode) => {
+                if !provinces
+                    .game_modes(&room.province_state)
+                    .contains(game_mode)
+                {
+                    continue;
+                }
+                room.game_mode = *game_mode;
+
+                let message = bincode::serialize(&ServerMessages::RoomGameMode {
+                    game_mode: *game_mode,
+                })
+                .unwrap();
+                server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+            }
+            RoomAction::Start => {
+                if *room_state.get() != RoomState::Waiting || !lobby.is_everyone_ready() {
+                    continue;
+                }
+                room.start_countdown(ROOM_COUNTDOWN_SECONDS);
+                next_state_room.set(RoomState::Countdown);
+
+                let message = bincode::serialize(&ServerMessages::RoomCountdown {
+                    seconds: ROOM_COUNTDOWN_SECONDS,
+                })
+                .unwrap();
+                server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+            }
+        }
+    }
+}
+
+fn broadcast_room_player(server: &mut RenetServer, lobby: &Lobby, player_id: PlayerId) {
+    if let Some(player_data) = lobby.players.get(&player_id) {
+        let message = bincode::serialize(&ServerMessages::RoomPlayerUpdate {
+            id: player_id,
+            color: player_data.color,
+            ready: player_data.ready,
+            team: player_data.team,
+        })
+        .unwrap();
+        server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+    }
+}
+
+/// Players can only pick an existing team, or none when teams are disabled.
+fn is_valid_team(team: Option<TeamId>, team_count: u8) -> bool {
+    match team {
+        Some(TeamId(team)) => team < team_count,
+        None => team_count == 0,
+    }
+}
+
+fn update_countdown(
+    lobby: Res<Lobby>,
+    room: Res<Room>,
+    mut server: ResMut<RenetServer>,
+    mut next_state_room: ResMut<NextState<RoomState>>,
+    mut next_state_province: ResMut<NextState<ProvinceState>>,
+    mut next_state_game_mode: ResMut<NextState<GameModeState>>,
+) {
+    if !lobby.is_everyone_ready() {
+        next_state_room.set(RoomState::Waiting);
+
+        let message = bincode::serialize(&ServerMessages::RoomCountdownCanceled).unwrap();
+        server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+    } else if room.countdown.finished() {
+        next_state_province.set(room.province_state.clone());
+        next_state_game_mode.set(room.game_mode);
+        next_state_room.set(RoomState::Playing);
+
+        let message = bincode::serialize(&ServerMessages::StartMatch {
+            province_state: room.province_state.clone(),
+            game_mode: room.game_mode,
+        })
+        .unwrap();
+        server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+    }
+}
+
+fn spawn_characters(
+    mut commands: Commands,
+    mut lobby: ResMut<Lobby>,
+    mut server: ResMut<RenetServer>,
+    spawn_point: Res<SpawnPoint>,
+    character_query: Query<&Position, With<Character>>,
+) {
+    // wait for the province to provide spawn points
+    if spawn_point.is_empty() {
+        return;
+    }
+
+    let mut occupied: Vec<Vec3> = character_query.iter().map(|position| position.0).collect();
+    for (player_id, player_data) in lobby.players.iter_mut() {
+        if player_data.entity.is_some() {
+            continue;
+        }
+
+        let location = spawn_point.choose(player_data.team, &occupied);
+        occupied.push(location.position);
+        let player_entity = commands
+            .spawn_character(
+                *player_id,
+                player_data.character_color(),
+                player_data.appearance.clone(),
+                location.position,
+            )
+            .insert(PlayerInput {
+                yaw: location.yaw,
+                ..default()
+            })
+            .id();
+        match player_id {
+            PlayerId::Host => {
+                commands.entity(player_entity).insert(Me);
+                commands.spawn_tied_camera(player_entity);
+            }
+            // the client steers its own view, it is told where to look
+            PlayerId::Client(client_id) => {
+                let message =
+                    bincode::serialize(&ServerMessages::Facing { yaw: location.yaw }).unwrap();
+                server.send_message(*client_id, DefaultChannel::ReliableOrdered, message);
+            }
+        }
+        player_data.entity = Some(player_entity);
+    }
+}
+
 pub fn send_change_province(
     mut change_province_event: EventReader<ChangeProvinceServerEvent>,
     mut character_respawn_query: Query<&mut Respawn, With<Character>>,
     mut server: ResMut<RenetServer>,
+    mut room: ResMut<Room>,
 ) {
     for ChangeProvinceServerEvent(state) in change_province_event.read() {
+        room.province_state = state.clone();
+
         let message = bincode::serialize(&ServerMessages::ChangeProvince {
-            province_state: *state,
+            province_state: state.clone(),
         })
         .unwrap();
         server.broadcast_message(DefaultChannel::ReliableOrdered, message);
@@ -236,21 +588,113 @@ pub fn send_change_province(
     }
 }
 
+pub fn send_change_game_mode(
+    mut change_game_mode_event: EventReader<ChangeGameModeServerEvent>,
+    mut server: ResMut<RenetServer>,
+    mut room: ResMut<Room>,
+) {
+    for ChangeGameModeServerEvent(game_mode) in change_game_mode_event.read() {
+        room.game_mode = *game_mode;
+
+        let message = bincode::serialize(&ServerMessages::ChangeGameMode {
+            game_mode: *game_mode,
+        })
+        .unwrap();
+        server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+    }
+}
+
+fn send_round_update(
+    mut server: ResMut<RenetServer>,
+    round: Res<Round>,
+    round_timer: Res<RoundTimer>,
+    round_phase: Res<State<RoundPhase>>,
+) {
+    let message = bincode::serialize(&ServerMessages::RoundUpdate {
+        phase: *round_phase.get(),
+        round: round.clone(),
+        remaining: round_timer.remaining_secs(),
+    })
+    .unwrap();
+    server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+}
+
+fn send_zone_update(mut server: ResMut<RenetServer>, zones: Res<CaptureZones>) {
+    let message = bincode::serialize(&ServerMessages::ZoneUpdate {
+        zones: zones.clone(),
+    })
+    .unwrap();
+    server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+}
+
+fn send_character_config(mut server: ResMut<RenetServer>, character_config: Res<CharacterConfig>) {
+    let message = bincode::serialize(&ServerMessages::ChangeCharacterConfig {
+        config: character_config.clone(),
+    })
+    .unwrap();
+    server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+}
+
+fn send_physics(mut server: ResMut<RenetServer>, physics: Res<ProvincePhysics>) {
+    let message = bincode::serialize(&ServerMessages::ChangePhysics {
+        physics: physics.clone(),
+    })
+    .unwrap();
+    server.broadcast_message(DefaultChannel::ReliableOrdered, message);
+}
+
 pub fn server_sync_players(
     mut server: ResMut<RenetServer>,
     // TODO a nahooya tut resours, daun
     mut data: ResMut<TransportDataResource>,
-    character_query: Query<(&Position, &Rotation, &PlayerViewDirection, &Character)>,
-    moveble_object_query: Query<(&Transform, &LinkId)>,
+    mut character_query: Query<(
+        &Position,
+        &Rotation,
+        &PlayerViewDirection,
+        &AbilityCooldowns,
+        &Damage,
+        &mut Ping,
+        &CheckpointProgress,
+        &ShotStats,
+        &Character,
+    )>,
+    moveble_object_query: Query<(&Transform, &LinkId), Without<Mover>>,
+    trigger_query: Query<(&MoverTrigger, &LinkId)>,
+    target_query: Query<(&TargetState, &LinkId)>,
+    clock: Res<MoverClock>,
 ) {
     let data = &mut data.data;
-    for (position, rotation, view_direction, character) in character_query.iter() {
+    for (
+        position,
+        rotation,
+        view_direction,
+        cooldowns,
+        damage,
+        mut ping,
+        checkpoint,
+        shots,
+        character,
+    ) in character_query.iter_mut()
+    {
+        let rtt = character
+            .id
+            .client_id()
+            .and_then(|client_id| server.network_info(client_id).ok())
+            .map_or(0, |network_info| network_info.rtt.round() as u32);
+        if ping.0 != rtt {
+            ping.0 = rtt;
+        }
         data.players.insert(
             character.id,
             PlayerTransportData {
                 position: position.0,
                 rotation: rotation.0,
-                tied_camera_rotation: view_direction.0,
+                view_direction: *view_direction,
+                cooldowns: *cooldowns,
+                damage: *damage,
+                ping: *ping,
+                checkpoint: *checkpoint,
+                shots: *shots,
             },
         );
     }
@@ -265,9 +709,20 @@ pub fn server_sync_players(
         );
     }
 
+    // movers follow the clock, only their triggers are sent
+    data.clock = clock.0;
+    for (trigger, link_id) in trigger_query.iter() {
+        data.triggers.insert(link_id.clone(), *trigger);
+    }
+    for (state, link_id) in target_query.iter() {
+        data.targets.insert(link_id.clone(), *state);
+    }
+
     let sync_message = bincode::serialize(&data).unwrap();
     server.broadcast_message(DefaultChannel::Unreliable, sync_message);
 
     data.players.clear();
     data.objects.clear();
+    data.triggers.clear();
+    data.targets.clear();
 }
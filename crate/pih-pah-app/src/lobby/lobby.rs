@@ -1,8 +1,10 @@
+use crate::character::{AbilityCooldowns, Appearance, CharacterConfig, Damage, ShotStats};
+use crate::game_mode::{CaptureZones, GameModeState, Round, RoundPhase};
 use crate::lobby::single::SingleLobbyPlugins;
-use crate::province::ProvinceState;
-use crate::world::LinkId;
+use crate::province::{ProvincePhysics, ProvinceState};
+use crate::world::{CheckpointProgress, LinkId, MoverTrigger, TargetState};
 use bevy::app::{App, Plugin};
-use bevy::math::{Quat, Vec3};
+use bevy::math::{Quat, Vec2, Vec3};
 use bevy::prelude::{Color, Component, Entity, Resource, States};
 use renet::transport::NETCODE_USER_DATA_BYTES;
 use renet::ClientId;
@@ -11,8 +13,10 @@ use std::collections::HashMap;
 
 use super::client::ClientLobbyPlugins;
 use super::host::HostLobbyPlugins;
+use super::room::{RoomPlugins, RoomState};
+use super::team::TeamId;
 
-pub const PROTOCOL_ID: u64 = 7;
+pub const PROTOCOL_ID: u64 = 8;
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum LobbyState {
@@ -28,56 +32,108 @@ pub enum ServerMessages {
     InitConnection {
         id: ClientId,
         province_state: ProvinceState,
+        game_mode: GameModeState,
+        room_state: RoomState,
     },
     ChangeProvince {
         province_state: ProvinceState,
     },
+    ChangeGameMode {
+        game_mode: GameModeState,
+    },
     PlayerConnected {
         id: PlayerId,
         color: Color,
         username: String,
+        ready: bool,
+        team: Option<TeamId>,
+        appearance: Appearance,
     },
     PlayerDisconnected {
         id: PlayerId,
     },
+    RoomPlayerUpdate {
+        id: PlayerId,
+        color: Color,
+        ready: bool,
+        team: Option<TeamId>,
+    },
+    RoomTeams {
+        team_count: u8,
+    },
+    RoomProvince {
+        province_state: ProvinceState,
+    },
+    RoomGameMode {
+        game_mode: GameModeState,
+    },
+    RoomCountdown {
+        seconds: f32,
+    },
+    RoomCountdownCanceled,
+    StartMatch {
+        province_state: ProvinceState,
+        game_mode: GameModeState,
+    },
+    RoundUpdate {
+        phase: RoundPhase,
+        round: Round,
+        /// Seconds left in the phase
+        remaining: Option<f32>,
+    },
+    ZoneUpdate {
+        zones: CaptureZones,
+    },
+    ChangeCharacterConfig {
+        config: CharacterConfig,
+    },
+    ChangePhysics {
+        physics: ProvincePhysics,
+    },
+    /// View yaw of the spawn point the character of the receiver was spawned at
+    Facing {
+        yaw: f32,
+    },
 }
 
-#[derive(Resource)]
-pub struct Username(pub String);
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ClientMessages {
+    Input(PlayerInput),
+    RoomUpdate {
+        color: Color,
+        ready: bool,
+        team: Option<TeamId>,
+    },
+}
 
-impl Default for Username {
-    fn default() -> Self {
-        Self("noname".to_string())
-    }
+/// Player info sent in the netcode user data, so the host knows it on connection.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConnectData {
+    pub username: String,
+    pub appearance: Appearance,
 }
 
-impl Username {
+impl ConnectData {
     pub fn to_netcode_data(
         &self,
     ) -> Result<[u8; NETCODE_USER_DATA_BYTES], Box<dyn std::error::Error>> {
         let mut data = [0u8; NETCODE_USER_DATA_BYTES];
-        if self.0.len() > NETCODE_USER_DATA_BYTES - 8 {
-            let err = Err(From::from("Your username to long"));
+        let bytes = bincode::serialize(self)?;
+        if bytes.len() > NETCODE_USER_DATA_BYTES {
+            let err = Err(From::from("Your username or model name to long"));
             log::error!("{:?}", err);
             return err;
         }
-        data[0..8].copy_from_slice(&(self.0.len() as u64).to_le_bytes());
-        data[8..self.0.len() + 8].copy_from_slice(self.0.as_bytes());
+        data[..bytes.len()].copy_from_slice(&bytes);
 
         Ok(data)
     }
 
     pub fn from_user_data(
         user_data: &[u8; NETCODE_USER_DATA_BYTES],
-    ) -> Result<String, Box<dyn std::error::Error>> {
-        let mut buffer = [0u8; 8];
-        buffer.copy_from_slice(&user_data[0..8]);
-        let mut len = u64::from_le_bytes(buffer) as usize;
-        len = len.min(NETCODE_USER_DATA_BYTES - 8);
-        let data = user_data[8..len + 8].to_vec();
-        let username = String::from_utf8(data)?;
-
-        Ok(username)
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        // the zero padding after the data is ignored
+        Ok(bincode::deserialize(user_data)?)
     }
 }
 
@@ -100,7 +156,12 @@ impl Plugin for LobbyPlugins {
         app.add_state::<LobbyState>()
             .init_resource::<HostResource>()
             .init_resource::<ClientResource>()
-            .add_plugins((SingleLobbyPlugins, HostLobbyPlugins, ClientLobbyPlugins));
+            .add_plugins((
+                SingleLobbyPlugins,
+                HostLobbyPlugins,
+                ClientLobbyPlugins,
+                RoomPlugins,
+            ));
     }
 }
 
@@ -127,23 +188,80 @@ impl PlayerId {
 
 #[derive(Debug)]
 pub struct PlayerData {
-    pub entity: Entity,
+    /// Character of the player, `None` until the match starts
+    pub entity: Option<Entity>,
     pub color: Color,
     pub username: String,
+    pub ready: bool,
+    pub team: Option<TeamId>,
+    pub appearance: Appearance,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Component, Resource)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Component, Resource)]
 pub struct PlayerInput {
     pub up: bool,
     pub down: bool,
     pub left: bool,
     pub right: bool,
+    /// Held, the server tracks presses so none is lost between fixed steps
     pub jump: bool,
     pub sprint: bool,
     pub turn_left: bool,
     pub turn_right: bool,
+    /// Held, the server tracks presses with a `ButtonEdge`
+    pub dash: bool,
+    /// Held, the server tracks presses with a `ButtonEdge`
+    pub ground_pound: bool,
+    /// Held while carrying a grabbed body
+    pub grab: bool,
+    /// Held, the server tracks presses with a `ButtonEdge`
+    pub shove: bool,
+    /// Held, back to the first spawn point forgetting the checkpoints
+    pub restart: bool,
+    /// Held, shoots again whenever the weapon cooldown allows
+    pub fire: bool,
+    /// Forward of the local camera, checked by the host against the view angles
+    pub aim: Vec3,
+    /// Position of the local camera, checked by the host against the character position
+    pub aim_origin: Vec3,
+    /// View angles in radians, steered by the local player
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Analog movement, x to the right and y forward, the stick tilt is the speed
+    pub move_x: f32,
+    pub move_y: f32,
+}
+
+/// Press of a held `PlayerInput` button, seen by the simulating side at a fixed step.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ButtonEdge(bool);
+
+impl ButtonEdge {
+    /// Whether the button went down since the last step, the held state survives
+    /// lost or repeated input messages, unlike a press flag.
+    pub fn pressed(&mut self, held: bool) -> bool {
+        let pressed = held && !self.0;
+        self.0 = held;
+        pressed
+    }
 }
 
+impl PlayerInput {
+    /// Movement in view space, x to the right and y backward, at most of length 1.
+    pub fn movement(&self) -> Vec2 {
+        let digital = Vec2::new(
+            (self.right as i8 - self.left as i8) as f32,
+            (self.down as i8 - self.up as i8) as f32,
+        );
+        let analog = Vec2::new(self.move_x, -self.move_y);
+        (digital + analog).clamp_length_max(1.)
+    }
+}
+
+/// Round trip time of the player connection in milliseconds, replicated for nameplates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component, Serialize, Deserialize)]
+pub struct Ping(pub u32);
+
 #[derive(Debug, Component)]
 pub struct Character {
     pub id: PlayerId,
@@ -153,7 +271,12 @@ pub struct Character {
 pub struct PlayerTransportData {
     pub position: Vec3,
     pub rotation: Quat,
-    pub tied_camera_rotation: Quat,
+    pub view_direction: PlayerViewDirection,
+    pub cooldowns: AbilityCooldowns,
+    pub damage: Damage,
+    pub ping: Ping,
+    pub checkpoint: CheckpointProgress,
+    pub shots: ShotStats,
 }
 
 #[derive(Resource, Default, Debug, Serialize, Deserialize)]
@@ -166,6 +289,10 @@ pub struct ObjectTransportData {
 pub struct TransportData {
     pub players: HashMap<PlayerId, PlayerTransportData>,
     pub objects: HashMap<LinkId, ObjectTransportData>,
+    /// `MoverClock` of the host, movers are not sent as objects
+    pub clock: f32,
+    pub triggers: HashMap<LinkId, MoverTrigger>,
+    pub targets: HashMap<LinkId, TargetState>,
 }
 
 #[derive(Resource, Default, Debug, Serialize, Deserialize)]
@@ -173,5 +300,33 @@ pub struct TransportDataResource {
     pub data: TransportData,
 }
 
-#[derive(Debug, Component, Default)]
-pub struct PlayerViewDirection(pub Quat);
+/// Camera pitch limits, relative to the default tied camera angle.
+pub const VIEW_PITCH_MIN: f32 = -0.89;
+pub const VIEW_PITCH_MAX: f32 = 0.94;
+
+#[derive(Debug, Component, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct PlayerViewDirection {
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl PlayerViewDirection {
+    /// Heading only, used for movement.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw)
+    }
+
+    /// Heading with pitch, used for the camera.
+    pub fn look(&self) -> Quat {
+        self.rotation() * Quat::from_rotation_x(self.pitch)
+    }
+}
+
+impl From<&PlayerInput> for PlayerViewDirection {
+    fn from(input: &PlayerInput) -> Self {
+        Self {
+            yaw: input.yaw,
+            pitch: input.pitch.clamp(VIEW_PITCH_MIN, VIEW_PITCH_MAX),
+        }
+    }
+}
@@ -4,6 +4,8 @@ mod lobby;
 
 pub mod client;
 pub mod host;
+pub mod room;
 pub mod single;
+pub mod team;
 
 pub use lobby::*;
@@ -0,0 +1,98 @@
+use crate::game_mode::GameModeState;
+use crate::lobby::host::generate_player_color;
+use crate::lobby::Lobby;
+use crate::province::ProvinceState;
+use bevy::app::{App, Plugin, Update};
+use bevy::ecs::event::Event;
+use bevy::ecs::schedule::States;
+use bevy::ecs::system::{Res, ResMut, Resource};
+use bevy::prelude::{in_state, Color, IntoSystemConfigs};
+use bevy::time::{Time, Timer, TimerMode};
+use serde::{Deserialize, Serialize};
+
+use super::team::TeamId;
+use super::PlayerId;
+
+pub const ROOM_COUNTDOWN_SECONDS: f32 = 5.;
+
+/// Pre-game phase of a multiplayer lobby.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States, Serialize, Deserialize)]
+pub enum RoomState {
+    #[default]
+    None = 0,
+    /// Players are gathering, picking colors and toggling ready
+    Waiting = 1,
+    /// Everyone is ready and the host has started the match
+    Countdown = 2,
+    /// Province is loaded and characters are spawned
+    Playing = 3,
+}
+
+#[derive(Debug, Resource)]
+pub struct Room {
+    /// Player controlled by this machine, known to the client after `InitConnection`
+    pub me: Option<PlayerId>,
+    pub province_state: ProvinceState,
+    pub game_mode: GameModeState,
+    /// Teams are disabled when zero
+    pub team_count: u8,
+    pub countdown: Timer,
+}
+
+impl Default for Room {
+    fn default() -> Self {
+        Self {
+            me: None,
+            province_state: ProvinceState::Menu,
+            game_mode: GameModeState::default(),
+            team_count: 0,
+            countdown: Timer::from_seconds(ROOM_COUNTDOWN_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+impl Room {
+    pub fn start_countdown(&mut self, seconds: f32) {
+        self.countdown = Timer::from_seconds(seconds, TimerMode::Once);
+    }
+}
+
+impl Lobby {
+    pub fn is_everyone_ready(&self) -> bool {
+        !self.players.is_empty() && self.players.values().all(|player| player.ready)
+    }
+}
+
+/// Local player's intent in the room, handled by the host or sent to it by the client.
+#[derive(Debug, Clone, Event)]
+pub enum RoomAction {
+    Ready(bool),
+    Color(Color),
+    Team(TeamId),
+    TeamCount(u8),
+    Province(ProvinceState),
+    GameMode(GameModeState),
+    Start,
+}
+
+/// Colors selectable in the room.
+pub fn room_palette() -> Vec<Color> {
+    (1..=12).map(generate_player_color).collect()
+}
+
+pub struct RoomPlugins;
+
+impl Plugin for RoomPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_state::<RoomState>()
+            .add_event::<RoomAction>()
+            .add_systems(
+                Update,
+                tick_countdown.run_if(in_state(RoomState::Countdown)),
+            );
+    }
+}
+
+fn tick_countdown(time: Res<Time>, mut room: ResMut<Room>) {
+    room.countdown.tick(time.delta());
+}
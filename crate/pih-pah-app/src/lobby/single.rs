@@ -1,6 +1,7 @@
 use crate::character::{spawn_character, spawn_tied_camera, TiedCamera};
 use crate::lobby::LobbyState;
 use crate::province::SpawnPoint;
+use crate::settings::Settings;
 use crate::world::Me;
 use bevy::app::{App, Plugin, Update};
 use bevy::ecs::entity::Entity;
@@ -8,7 +9,7 @@ use bevy::ecs::query::With;
 use bevy::ecs::schedule::OnExit;
 use bevy::ecs::system::{Query, Res};
 use bevy::hierarchy::DespawnRecursiveExt;
-use bevy::prelude::{in_state, Color, Commands, IntoSystemConfigs, OnEnter};
+use bevy::prelude::{default, in_state, Color, Commands, IntoSystemConfigs, OnEnter};
 
 use super::{PlayerId, PlayerInput};
 
@@ -22,10 +23,22 @@ impl Plugin for SingleLobbyPlugins {
     }
 }
 
-fn setup(mut commands: Commands, spawn_point: Res<SpawnPoint>) {
+fn setup(mut commands: Commands, spawn_point: Res<SpawnPoint>, settings: Res<Settings>) {
+    let location = spawn_point.choose(None, &[]);
     let entity = commands
-        .spawn_character(PlayerId::Host, Color::RED, spawn_point.random_point())
-        .insert(Me)
+        .spawn_character(
+            PlayerId::Host,
+            Color::RED,
+            settings.appearance.clone(),
+            location.position,
+        )
+        .insert((
+            Me,
+            PlayerInput {
+                yaw: location.yaw,
+                ..default()
+            },
+        ))
         .id();
     commands.spawn_tied_camera(entity);
 }
@@ -0,0 +1,76 @@
+use std::fmt::Display;
+
+use bevy::prelude::Color;
+use serde::{Deserialize, Serialize};
+
+use super::{Lobby, PlayerData, PlayerId};
+
+pub const MAX_TEAMS: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TeamId(pub u8);
+
+impl TeamId {
+    pub fn color(&self) -> Color {
+        match self.0 % MAX_TEAMS {
+            0 => Color::RED,
+            1 => Color::BLUE,
+            2 => Color::GREEN,
+            _ => Color::YELLOW,
+        }
+    }
+
+    pub fn all(team_count: u8) -> impl Iterator<Item = TeamId> {
+        (0..team_count.min(MAX_TEAMS)).map(TeamId)
+    }
+}
+
+impl Display for TeamId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 % MAX_TEAMS {
+            0 => write!(f, "Red"),
+            1 => write!(f, "Blue"),
+            2 => write!(f, "Green"),
+            _ => write!(f, "Yellow"),
+        }
+    }
+}
+
+impl PlayerData {
+    /// Team color overrides the color picked by the player.
+    pub fn character_color(&self) -> Color {
+        self.team.map_or(self.color, |team| team.color())
+    }
+}
+
+impl Lobby {
+    pub fn team_size(&self, team: TeamId) -> usize {
+        self.players
+            .values()
+            .filter(|player| player.team == Some(team))
+            .count()
+    }
+
+    /// Team for a newly joined player, `None` when teams are disabled.
+    pub fn smallest_team(&self, team_count: u8) -> Option<TeamId> {
+        TeamId::all(team_count).min_by_key(|team| self.team_size(*team))
+    }
+
+    /// Spread all players evenly across `team_count` teams.
+    pub fn balance_teams(&mut self, team_count: u8) {
+        let mut player_ids: Vec<PlayerId> = self.players.keys().copied().collect();
+        // host first, then clients by join order
+        player_ids.sort_by_key(|player_id| player_id.client_id().map(|id| id.raw()));
+
+        let teams: Vec<TeamId> = TeamId::all(team_count).collect();
+        for (index, player_id) in player_ids.iter().enumerate() {
+            if let Some(player_data) = self.players.get_mut(player_id) {
+                player_data.team = if teams.is_empty() {
+                    None
+                } else {
+                    Some(teams[index % teams.len()])
+                };
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@ use bevy::winit::WinitWindows;
 use bevy_egui::EguiPlugin;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_xpbd_3d::prelude::PhysicsPlugins;
+use pih_pah_app::util::ASSET_FOLDER;
 use pih_pah_app::world::WorldPlugins;
 use winit::window::Icon;
 
@@ -25,7 +26,7 @@ fn main() {
     if !is_debug {
         app.add_plugins((
             DefaultPlugins.set(AssetPlugin {
-                file_path: "asset".into(),
+                file_path: ASSET_FOLDER.into(),
                 ..default()
             }),
             EguiPlugin,
@@ -46,7 +47,7 @@ fn main() {
         };
         app.add_plugins((
             DefaultPlugins.set(window_plugin_override).set(AssetPlugin {
-                file_path: "asset".into(),
+                file_path: ASSET_FOLDER.into(),
                 ..default()
             }),
             EguiPlugin,
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::lobby::LobbyState;
+use crate::world::{sensor_from_mesh, SceneTagAppExt, SceneTagContext, SceneTagErrorKind};
+
+use super::DEFAULT_GRAVITY;
+
+/// Volume tagged `g:<kind>[,<strength>]` replacing the gravity of the bodies inside.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub enum GravityZone {
+    /// Bodies float
+    Zero,
+    /// Pulls along the down axis of the node
+    Directional(f32),
+    /// Pulls toward the origin of the node, pushes away when negative
+    Radial(f32),
+}
+
+impl GravityZone {
+    pub fn gravity(&self, zone: &GlobalTransform, position: Vec3) -> Vec3 {
+        match self {
+            GravityZone::Zero => Vec3::ZERO,
+            GravityZone::Directional(strength) => zone.down() * *strength,
+            GravityZone::Radial(strength) => {
+                (zone.translation() - position).normalize_or_zero() * *strength
+            }
+        }
+    }
+}
+
+/// Gravity of a dynamic body inside gravity zones, the province gravity applies without it.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct GravityOverride(pub Vec3);
+
+impl GravityOverride {
+    /// Gravity acting on a body, `global` when it is in no zone.
+    pub fn resolve(gravity_override: Option<&GravityOverride>, global: Vec3) -> Vec3 {
+        gravity_override.map_or(global, |gravity_override| gravity_override.0)
+    }
+}
+
+/// Force the gravity zones add to the `ExternalForce` of a body, taken back when it leaves.
+#[derive(Debug, Clone, Copy, Component)]
+struct ZoneForce(Vec3);
+
+pub struct GravityZonePlugins;
+
+impl Plugin for GravityZonePlugins {
+    fn build(&self, app: &mut App) {
+        app.register_scene_tag("g", gravity_zone_tag).add_systems(
+            FixedUpdate,
+            apply_gravity_zones.run_if(not(in_state(LobbyState::Client))),
+        );
+    }
+}
+
+/// `g:zero`, `g:dir[,<m/s²>]` or `g:radial[,<m/s²>]`, the strength defaults to earth gravity.
+fn gravity_zone_tag(
+    commands: &mut Commands,
+    tag: &SceneTagContext,
+) -> Result<(), SceneTagErrorKind> {
+    let value = tag.text()?;
+    let (kind, strength) = match value.split_once(',') {
+        Some((kind, strength)) => (
+            kind.trim(),
+            strength
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|strength| strength.is_finite())
+                .ok_or_else(|| tag.invalid(value))?,
+        ),
+        None => (value, DEFAULT_GRAVITY),
+    };
+    let zone = match kind {
+        "zero" if !value.contains(',') => GravityZone::Zero,
+        "dir" => GravityZone::Directional(strength),
+        "radial" => GravityZone::Radial(strength),
+        _ => return Err(tag.invalid(value)),
+    };
+
+    commands
+        .entity(tag.entity)
+        .insert((zone, sensor_from_mesh(tag)?));
+    Ok(())
+}
+
+/// Overlapping zones add up, the difference to the province gravity is applied as a force.
+/// Only the change of the zone force is applied, other forces on the body are kept.
+fn apply_gravity_zones(
+    mut commands: Commands,
+    gravity: Res<Gravity>,
+    collisions: Res<Collisions>,
+    zone_query: Query<(Entity, &GravityZone, &GlobalTransform)>,
+    mut body_query: Query<(
+        Entity,
+        &RigidBody,
+        &Mass,
+        &GlobalTransform,
+        &mut ExternalForce,
+        Option<&GravityOverride>,
+        Option<&mut ZoneForce>,
+    )>,
+) {
+    let mut overrides: HashMap<Entity, Vec3> = HashMap::new();
+    for (zone_entity, zone, zone_transform) in zone_query.iter() {
+        for contacts in collisions.collisions_with_entity(zone_entity) {
+            let other = if contacts.entity1 == zone_entity {
+                contacts.entity2
+            } else {
+                contacts.entity1
+            };
+            let Ok((_, rigid_body, _, transform, ..)) = body_query.get(other) else {
+                continue;
+            };
+            if !rigid_body.is_dynamic() {
+                continue;
+            }
+            *overrides.entry(other).or_default() +=
+                zone.gravity(zone_transform, transform.translation());
+        }
+    }
+
+    for (entity, _, mass, _, mut external_force, gravity_override, zone_force) in
+        body_query.iter_mut()
+    {
+        let applied = zone_force
+            .as_ref()
+            .map_or(Vec3::ZERO, |zone_force| zone_force.0);
+        match overrides.get(&entity) {
+            Some(zone_gravity) => {
+                if gravity_override.map(|gravity_override| gravity_override.0)
+                    != Some(*zone_gravity)
+                {
+                    commands
+                        .entity(entity)
+                        .insert(GravityOverride(*zone_gravity));
+                }
+                let force = (*zone_gravity - gravity.0) * mass.0;
+                if force != applied {
+                    external_force.apply_force(force - applied);
+                    match zone_force {
+                        Some(mut zone_force) => zone_force.0 = force,
+                        None => {
+                            commands.entity(entity).insert(ZoneForce(force));
+                        }
+                    }
+                }
+            }
+            None if gravity_override.is_some() || zone_force.is_some() => {
+                commands
+                    .entity(entity)
+                    .remove::<(GravityOverride, ZoneForce)>();
+                external_force.apply_force(-applied);
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs::File;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::character::{JumpConfig, WeaponConfig};
+use crate::game_mode::GameModeState;
+use crate::util::asset_folder;
+
+use super::{ProvincePhysics, ProvinceState, SPAWN_CLEARANCE};
+
+/// Folder of the province manifests, relative to the asset folder.
+pub const PROVINCE_FOLDER: &str = "province";
+/// Picked for single player and new rooms when its manifest exists
+pub const DEFAULT_PROVINCE: &str = "shooting_range";
+
+/// File stem of a province manifest, the same on every machine.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ProvinceId(pub String);
+
+impl Display for ProvinceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProvinceLight {
+    pub illuminance: f32,
+    /// Rotation of the sun around the x axis
+    pub pitch_degrees: f32,
+    pub shadows: bool,
+}
+
+impl Default for ProvinceLight {
+    fn default() -> Self {
+        Self {
+            illuminance: 4000.,
+            pitch_degrees: -45.,
+            shadows: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpawnRules {
+    /// Used when the scene has no `spawn` tag
+    pub fallback: Vec3,
+    /// Distance to other players a spawn point must keep while others are free
+    pub clearance: f32,
+}
+
+impl Default for SpawnRules {
+    fn default() -> Self {
+        Self {
+            fallback: Vec3::new(0., 30., 0.),
+            clearance: SPAWN_CLEARANCE,
+        }
+    }
+}
+
+/// Province declared by a YAML file in the province folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvinceManifest {
+    pub name: String,
+    /// glTF file relative to the asset folder
+    pub scene: String,
+    #[serde(default)]
+    pub light: ProvinceLight,
+    #[serde(default)]
+    pub physics: ProvincePhysics,
+    #[serde(default)]
+    pub spawn: SpawnRules,
+    #[serde(default)]
+    pub jump: JumpConfig,
+    /// Characters are unarmed without it
+    #[serde(default)]
+    pub weapon: Option<WeaponConfig>,
+    /// Looped while the province is loaded, relative to the asset folder
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Every game mode when empty
+    #[serde(default)]
+    pub game_modes: Vec<GameModeState>,
+}
+
+impl ProvinceManifest {
+    pub fn game_modes(&self) -> Vec<GameModeState> {
+        if self.game_modes.is_empty() {
+            GameModeState::all().to_vec()
+        } else {
+            self.game_modes.clone()
+        }
+    }
+
+    pub fn allows(&self, game_mode: GameModeState) -> bool {
+        self.game_modes.is_empty() || self.game_modes.contains(&game_mode)
+    }
+}
+
+/// Manifests found in the province folder at startup, by id.
+#[derive(Debug, Default, Resource)]
+pub struct Provinces(pub BTreeMap<ProvinceId, ProvinceManifest>);
+
+impl Provinces {
+    pub(super) fn scan() -> Self {
+        let folder = asset_folder(PROVINCE_FOLDER);
+        let Ok(entries) = std::fs::read_dir(&folder) else {
+            warn!("No province folder at {:?}", folder);
+            return Self::default();
+        };
+        let mut provinces = BTreeMap::new();
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            if !matches!(
+                path.extension().and_then(|extension| extension.to_str()),
+                Some("yaml" | "yml")
+            ) {
+                continue;
+            }
+            let Some(id) = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            let manifest = File::open(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|file| serde_yaml::from_reader(file).map_err(|err| err.to_string()));
+            match manifest {
+                Ok(manifest) => {
+                    provinces.insert(ProvinceId(id), manifest);
+                }
+                Err(err) => error!("Failed to read province manifest {:?}: {}", path, err),
+            }
+        }
+        Self(provinces)
+    }
+
+    pub fn get(&self, province_state: &ProvinceState) -> Option<&ProvinceManifest> {
+        match province_state {
+            ProvinceState::Menu => None,
+            ProvinceState::Province(id) => self.0.get(id),
+        }
+    }
+
+    /// Name of the manifest, or the id of a province missing on this machine.
+    pub fn name(&self, province_state: &ProvinceState) -> String {
+        self.get(province_state).map_or_else(
+            || province_state.to_string(),
+            |manifest| manifest.name.clone(),
+        )
+    }
+
+    pub fn states(&self) -> impl Iterator<Item = ProvinceState> + '_ {
+        self.0.keys().cloned().map(ProvinceState::Province)
+    }
+
+    /// The default province, or the first one when it is missing.
+    pub fn default_state(&self) -> ProvinceState {
+        let default = ProvinceId(DEFAULT_PROVINCE.to_string());
+        if self.0.contains_key(&default) {
+            return ProvinceState::Province(default);
+        }
+        self.states().next().unwrap_or_default()
+    }
+
+    /// Game modes playable in the province, all of them for an unknown one.
+    pub fn game_modes(&self, province_state: &ProvinceState) -> Vec<GameModeState> {
+        self.get(province_state).map_or_else(
+            || GameModeState::all().to_vec(),
+            ProvinceManifest::game_modes,
+        )
+    }
+}
@@ -1,4 +1,4 @@
-use super::ProvinceState;
+use super::{spawn_point::SpawnPoint, ProvinceState};
 use bevy::prelude::*;
 use std::f32::consts::PI;
 
@@ -32,6 +32,9 @@ fn load(
     mut mesh: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
+    // forget spawn points of the previous province
+    commands.insert_resource(SpawnPoint::default());
+
     commands
         .spawn(Camera3dBundle {
             transform: Transform::from_xyz(5., 2.5, 5.).looking_at(Vec3::ZERO, Vec3::Y),
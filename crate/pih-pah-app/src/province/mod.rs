@@ -1,13 +1,15 @@
 #![allow(clippy::module_inception)]
 
-mod gravity_hell;
+mod gravity_zone;
+mod manifest;
 mod menu;
+mod physics;
 mod province;
-mod shooting_range;
 mod spawn_point;
 
-pub use gravity_hell::*;
+pub use gravity_zone::*;
+pub use manifest::*;
 pub use menu::*;
+pub use physics::*;
 pub use province::*;
-pub use shooting_range::*;
 pub use spawn_point::*;
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Gravity of a province that does not set its own, in m/s².
+pub const DEFAULT_GRAVITY: f32 = 9.81;
+
+/// Physics of the loaded province, owned by the host and replicated to clients.
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProvincePhysics {
+    pub gravity: Vec3,
+    /// Solver substeps per physics step, more are steadier and slower
+    pub substeps: u32,
+    /// Speed of the physics clock, `1` is real time
+    pub time_scale: f32,
+}
+
+impl Default for ProvincePhysics {
+    fn default() -> Self {
+        Self {
+            gravity: Vec3::NEG_Y * DEFAULT_GRAVITY,
+            substeps: 12,
+            time_scale: 1.,
+        }
+    }
+}
+
+pub struct ProvincePhysicsPlugins;
+
+impl Plugin for ProvincePhysicsPlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProvincePhysics>().add_systems(
+            Update,
+            apply_physics.run_if(resource_changed::<ProvincePhysics>()),
+        );
+    }
+}
+
+fn apply_physics(
+    mut commands: Commands,
+    physics: Res<ProvincePhysics>,
+    mut time: ResMut<Time<Physics>>,
+) {
+    commands.insert_resource(Gravity(physics.gravity));
+    commands.insert_resource(SubstepCount(physics.substeps.max(1)));
+    time.set_relative_speed(physics.time_scale.max(0.));
+}
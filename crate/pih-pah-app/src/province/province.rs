@@ -1,37 +1,127 @@
 use std::fmt::Display;
 
+use crate::character::WeaponConfig;
+use crate::lobby::LobbyState;
 use crate::province::menu::MenuPlugins;
-use crate::province::ShootingRangePlugins;
-use bevy::prelude::*;
+use crate::world::PromisedScene;
+use bevy::{pbr::CascadeShadowConfigBuilder, prelude::*};
 use serde::{Deserialize, Serialize};
 
-use super::spawn_point::SpawnPoint;
-use super::GravityHellPlugins;
+use super::{
+    GravityZonePlugins, ProvinceId, ProvincePhysics, ProvincePhysicsPlugins, Provinces, SpawnPoint,
+    SpawnPointPlugins,
+};
 
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States, Serialize, Deserialize)]
+/// Loaded province, the id of a manifest once a game is started.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, States, Serialize, Deserialize)]
 pub enum ProvinceState {
     #[default]
-    Menu = 0,
-    ShootingRange = 1,
-    GravityHell = 2,
+    Menu,
+    Province(ProvinceId),
 }
 
 impl Display for ProvinceState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ProvinceState::Menu => write!(f, "Menu"),
-            ProvinceState::ShootingRange => write!(f, "ShootingRange"),
-            ProvinceState::GravityHell => write!(f, "GravityHell"),
+            ProvinceState::Province(id) => write!(f, "{}", id),
         }
     }
 }
 
+/// Everything spawned for the loaded manifest.
+#[derive(Component)]
+struct Affiliation;
+
 pub struct ProvincePlugins;
 
 impl Plugin for ProvincePlugins {
     fn build(&self, app: &mut App) {
         app.add_state::<ProvinceState>()
-            .init_resource::<SpawnPoint>()
-            .add_plugins((MenuPlugins, ShootingRangePlugins, GravityHellPlugins));
+            .insert_resource(Provinces::scan())
+            .add_plugins((
+                MenuPlugins,
+                SpawnPointPlugins,
+                ProvincePhysicsPlugins,
+                GravityZonePlugins,
+            ))
+            // right after the transition like `OnEnter`, so `Update` already sees the new province
+            .add_systems(
+                StateTransition,
+                (unload, load)
+                    .chain()
+                    .after(apply_state_transition::<ProvinceState>)
+                    .run_if(state_changed::<ProvinceState>()),
+            );
+    }
+}
+
+fn load(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    provinces: Res<Provinces>,
+    province_state: Res<State<ProvinceState>>,
+    lobby_state: Res<State<LobbyState>>,
+) {
+    if *province_state.get() == ProvinceState::Menu {
+        commands.insert_resource(ProvincePhysics::default());
+        commands.remove_resource::<WeaponConfig>();
+        return;
+    }
+    let Some(manifest) = provinces.get(province_state.get()) else {
+        warn!("No manifest for province {}", province_state.get());
+        return;
+    };
+
+    // points come from the `spawn` tags of the scene
+    commands.insert_resource(
+        SpawnPoint::with_fallback(manifest.spawn.fallback).with_clearance(manifest.spawn.clearance),
+    );
+    commands.insert_resource(manifest.jump.clone());
+    match &manifest.weapon {
+        Some(weapon) => commands.insert_resource(weapon.clone()),
+        None => commands.remove_resource::<WeaponConfig>(),
+    }
+    // clients get the physics of the host
+    if *lobby_state.get() != LobbyState::Client {
+        commands.insert_resource(manifest.physics.clone());
+    }
+
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                color: Color::WHITE,
+                illuminance: manifest.light.illuminance,
+                shadows_enabled: manifest.light.shadows,
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(0.0, 200.0, 0.0),
+                rotation: Quat::from_rotation_x(manifest.light.pitch_degrees.to_radians()),
+                ..default()
+            },
+            cascade_shadow_config: CascadeShadowConfigBuilder {
+                first_cascade_far_bound: 4.0,
+                ..default()
+            }
+            .into(),
+            ..default()
+        },
+        Affiliation,
+    ));
+
+    let scene = asset_server.load(format!("{}#Scene0", manifest.scene));
+
+    commands.spawn((
+        SceneBundle { scene, ..default() },
+        PromisedScene,
+        Affiliation,
+        Name::new(manifest.name.clone()),
+    ));
+}
+
+fn unload(mut commands: Commands, affiliation_query: Query<Entity, With<Affiliation>>) {
+    for entity in affiliation_query.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
@@ -1,65 +1,173 @@
-use bevy::{ecs::system::Resource, math::Vec3, prelude::Deref};
-use rand::Rng;
+use std::collections::HashMap;
 
-#[derive(Debug, Clone, Resource, Deref, Default)]
-pub struct SpawnPoint(Vec<Vec3>);
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::lobby::team::{TeamId, MAX_TEAMS};
+use crate::lobby::LobbyState;
+use crate::world::{SceneProcessed, SceneTagAppExt, SceneTagContext, SceneTagErrorKind};
+
+/// Default distance to other players a point must keep while others are free
+pub const SPAWN_CLEARANCE: f32 = 5.;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpawnLocation {
+    pub position: Vec3,
+    /// View yaw in radians, from the rotation of the tagged node
+    pub yaw: f32,
+}
+
+impl SpawnLocation {
+    pub fn new(position: Vec3) -> Self {
+        Self { position, yaw: 0. }
+    }
+}
+
+/// Spawn points of the province, filled from the `spawn` tags once its scene is processed.
+#[derive(Debug, Clone, Resource)]
+pub struct SpawnPoint {
+    points: Vec<SpawnLocation>,
+    team_points: HashMap<TeamId, Vec<SpawnLocation>>,
+    /// Used when the scene defines no spawn point
+    fallback: Vec3,
+    clearance: f32,
+}
+
+impl Default for SpawnPoint {
+    fn default() -> Self {
+        Self {
+            points: Vec::new(),
+            team_points: HashMap::new(),
+            fallback: Vec3::ZERO,
+            clearance: SPAWN_CLEARANCE,
+        }
+    }
+}
 
 impl SpawnPoint {
-    pub fn new<T: IntoVec3Vec>(spawn_points: T) -> Self {
-        Self(spawn_points.into_vec3_vec())
+    pub fn with_fallback(fallback: Vec3) -> Self {
+        Self {
+            fallback,
+            ..default()
+        }
+    }
+
+    pub fn with_clearance(self, clearance: f32) -> Self {
+        Self { clearance, ..self }
     }
 
+    /// Empty until the province scene is processed.
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.points.is_empty() && self.team_points.is_empty()
     }
 
-    pub fn points(&self) -> &[Vec3] {
-        &self.0
+    pub fn points(&self) -> &[SpawnLocation] {
+        &self.points
     }
 
-    pub fn random_point(&self) -> Vec3 {
-        let mut rng = rand::thread_rng();
-        let index = rng.gen_range(0..self.0.len());
-        self.0[index]
+    pub fn team_points(&self, team: TeamId) -> &[SpawnLocation] {
+        self.team_points
+            .get(&team)
+            .map_or(&[][..], |points| points.as_slice())
     }
-}
 
-pub trait IntoVec3Vec {
-    fn into_vec3_vec(self) -> Vec<Vec3>;
-}
+    /// Random point of the team, or a common one when it has none, away from `others` if possible.
+    pub fn choose(&self, team: Option<TeamId>, others: &[Vec3]) -> SpawnLocation {
+        let team_points = team.map_or(&[][..], |team| self.team_points(team));
+        // without common points, players out of a team share the team points
+        let candidates: Vec<SpawnLocation> = if !team_points.is_empty() {
+            team_points.to_vec()
+        } else if !self.points.is_empty() {
+            self.points.clone()
+        } else {
+            self.team_points.values().flatten().copied().collect()
+        };
 
-impl IntoVec3Vec for Vec3 {
-    fn into_vec3_vec(self) -> Vec<Vec3> {
-        vec![self]
+        let clearance = |location: &SpawnLocation| {
+            others
+                .iter()
+                .map(|other| other.distance(location.position))
+                .fold(f32::INFINITY, f32::min)
+        };
+        let free: Vec<_> = candidates
+            .iter()
+            .filter(|location| clearance(location) >= self.clearance)
+            .collect();
+        if let Some(location) = free.choose(&mut rand::thread_rng()) {
+            return **location;
+        }
+        // every point is taken, the least crowded one is the best bet
+        candidates
+            .iter()
+            .max_by(|a, b| clearance(a).total_cmp(&clearance(b)))
+            .copied()
+            .unwrap_or(SpawnLocation::new(self.fallback))
     }
 }
 
-impl IntoVec3Vec for (Vec3, Vec3) {
-    fn into_vec3_vec(self) -> Vec<Vec3> {
-        vec![self.0, self.1]
-    }
+/// Node tagged `spawn` or `spawn:<team index>`, facing along its forward axis.
+#[derive(Debug, Component)]
+pub struct SpawnMarker {
+    pub team: Option<TeamId>,
 }
 
-impl IntoVec3Vec for (Vec3, Vec3, Vec3) {
-    fn into_vec3_vec(self) -> Vec<Vec3> {
-        vec![self.0, self.1, self.2]
-    }
-}
+pub struct SpawnPointPlugins;
 
-impl IntoVec3Vec for (Vec3, Vec3, Vec3, Vec3) {
-    fn into_vec3_vec(self) -> Vec<Vec3> {
-        vec![self.0, self.1, self.2, self.3]
+impl Plugin for SpawnPointPlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnPoint>()
+            .register_scene_tag("spawn", spawn_tag)
+            .add_systems(
+                PostUpdate,
+                collect_spawn_points.run_if(not(in_state(LobbyState::Client))),
+            );
     }
 }
 
-impl IntoVec3Vec for (Vec3, Vec3, Vec3, Vec3, Vec3) {
-    fn into_vec3_vec(self) -> Vec<Vec3> {
-        vec![self.0, self.1, self.2, self.3, self.4]
-    }
+fn spawn_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    let team = match tag.value {
+        None => None,
+        Some(value) => match value.parse::<u8>() {
+            Ok(index) if index < MAX_TEAMS => Some(TeamId(index)),
+            _ => return Err(tag.invalid(value)),
+        },
+    };
+    commands.entity(tag.entity).insert(SpawnMarker { team });
+    Ok(())
 }
 
-impl IntoVec3Vec for (Vec3, Vec3, Vec3, Vec3, Vec3, Vec3) {
-    fn into_vec3_vec(self) -> Vec<Vec3> {
-        vec![self.0, self.1, self.2, self.3, self.4, self.5]
+fn collect_spawn_points(
+    mut scene_processed: EventReader<SceneProcessed>,
+    mut spawn_point: ResMut<SpawnPoint>,
+    parent_query: Query<&Children>,
+    marker_query: Query<(&SpawnMarker, &GlobalTransform)>,
+) {
+    for SceneProcessed(scene) in scene_processed.read() {
+        let mut points = Vec::new();
+        let mut team_points: HashMap<TeamId, Vec<SpawnLocation>> = HashMap::new();
+        for entity in parent_query.iter_descendants(*scene) {
+            let Ok((marker, transform)) = marker_query.get(entity) else {
+                continue;
+            };
+            let forward = transform.forward();
+            let location = SpawnLocation {
+                position: transform.translation(),
+                yaw: (-forward.x).atan2(-forward.z),
+            };
+            match marker.team {
+                Some(team) => team_points.entry(team).or_default().push(location),
+                None => points.push(location),
+            }
+        }
+
+        if points.is_empty() && team_points.is_empty() {
+            warn!(
+                "Scene has no spawn point, spawning at {}",
+                spawn_point.fallback
+            );
+            points.push(SpawnLocation::new(spawn_point.fallback));
+        }
+        spawn_point.points = points;
+        spawn_point.team_points = team_points;
     }
 }
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use bevy::ecs::system::{Res, SystemParam};
+use bevy::input::gamepad::{Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, Gamepads};
+use bevy::input::{Axis, Input};
+use bevy::math::Vec2;
+use bevy::prelude::{GamepadButtonType, KeyCode, MouseButton};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    TurnLeft,
+    TurnRight,
+    Jump,
+    Sprint,
+    Dash,
+    GroundPound,
+    Grab,
+    Shove,
+    Fire,
+    Restart,
+    ToggleCamera,
+    OpenMenu,
+    Chat,
+    Scoreboard,
+}
+
+impl Display for InputAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputAction::MoveForward => write!(f, "Move forward"),
+            InputAction::MoveBack => write!(f, "Move back"),
+            InputAction::MoveLeft => write!(f, "Move left"),
+            InputAction::MoveRight => write!(f, "Move right"),
+            InputAction::TurnLeft => write!(f, "Turn left"),
+            InputAction::TurnRight => write!(f, "Turn right"),
+            InputAction::Jump => write!(f, "Jump"),
+            InputAction::Sprint => write!(f, "Sprint"),
+            InputAction::Dash => write!(f, "Dash"),
+            InputAction::GroundPound => write!(f, "Ground pound"),
+            InputAction::Grab => write!(f, "Grab"),
+            InputAction::Shove => write!(f, "Shove"),
+            InputAction::Fire => write!(f, "Fire"),
+            InputAction::Restart => write!(f, "Back to start"),
+            InputAction::ToggleCamera => write!(f, "Toggle camera"),
+            InputAction::OpenMenu => write!(f, "Open menu"),
+            InputAction::Chat => write!(f, "Chat"),
+            InputAction::Scoreboard => write!(f, "Scoreboard"),
+        }
+    }
+}
+
+impl InputAction {
+    pub fn all() -> [InputAction; 18] {
+        [
+            InputAction::MoveForward,
+            InputAction::MoveBack,
+            InputAction::MoveLeft,
+            InputAction::MoveRight,
+            InputAction::TurnLeft,
+            InputAction::TurnRight,
+            InputAction::Jump,
+            InputAction::Sprint,
+            InputAction::Dash,
+            InputAction::GroundPound,
+            InputAction::Grab,
+            InputAction::Shove,
+            InputAction::Fire,
+            InputAction::Restart,
+            InputAction::ToggleCamera,
+            InputAction::OpenMenu,
+            InputAction::Chat,
+            InputAction::Scoreboard,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Button of any connected gamepad
+    Gamepad(GamepadButtonType),
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            Binding::Gamepad(button) => write!(f, "Pad {:?}", button),
+        }
+    }
+}
+
+/// Every device an action can be bound to.
+#[derive(SystemParam)]
+pub struct InputSources<'w> {
+    pub keys: Res<'w, Input<KeyCode>>,
+    pub mouse_buttons: Res<'w, Input<MouseButton>>,
+    pub gamepads: Res<'w, Gamepads>,
+    pub gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    pub gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl InputSources<'_> {
+    /// Gamepad driving the local player, the first connected one.
+    pub fn gamepad(&self) -> Option<Gamepad> {
+        self.gamepads.iter().next()
+    }
+
+    /// Stick position with a radial dead zone, rescaled to stay continuous past it.
+    pub fn stick(&self, x: GamepadAxisType, y: GamepadAxisType, dead_zone: f32) -> Vec2 {
+        let Some(gamepad) = self.gamepad() else {
+            return Vec2::ZERO;
+        };
+        let axis = |axis_type| {
+            self.gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default()
+        };
+        let stick = Vec2::new(axis(x), axis(y));
+        let length = stick.length();
+        if length <= dead_zone {
+            Vec2::ZERO
+        } else {
+            stick / length * ((length - dead_zone) / (1. - dead_zone).max(f32::EPSILON)).min(1.)
+        }
+    }
+
+    pub fn left_stick(&self, dead_zone: f32) -> Vec2 {
+        self.stick(
+            GamepadAxisType::LeftStickX,
+            GamepadAxisType::LeftStickY,
+            dead_zone,
+        )
+    }
+
+    pub fn right_stick(&self, dead_zone: f32) -> Vec2 {
+        self.stick(
+            GamepadAxisType::RightStickX,
+            GamepadAxisType::RightStickY,
+            dead_zone,
+        )
+    }
+}
+
+impl Binding {
+    pub fn pressed(&self, input: &InputSources) -> bool {
+        match self {
+            Binding::Key(key) => input.keys.pressed(*key),
+            Binding::Mouse(button) => input.mouse_buttons.pressed(*button),
+            Binding::Gamepad(button) => input.gamepads.iter().any(|gamepad| {
+                input
+                    .gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, *button))
+            }),
+        }
+    }
+
+    pub fn just_pressed(&self, input: &InputSources) -> bool {
+        match self {
+            Binding::Key(key) => input.keys.just_pressed(*key),
+            Binding::Mouse(button) => input.mouse_buttons.just_pressed(*button),
+            Binding::Gamepad(button) => input.gamepads.iter().any(|gamepad| {
+                input
+                    .gamepad_buttons
+                    .just_pressed(GamepadButton::new(gamepad, *button))
+            }),
+        }
+    }
+}
+
+/// Bindings of every action, any of them triggers the action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "HashMap<InputAction, Vec<Binding>>")]
+pub struct InputMap(pub HashMap<InputAction, Vec<Binding>>);
+
+impl From<HashMap<InputAction, Vec<Binding>>> for InputMap {
+    /// Actions missing from an older settings file keep their default bindings.
+    fn from(mut bindings: HashMap<InputAction, Vec<Binding>>) -> Self {
+        for (action, default) in InputMap::default().0 {
+            bindings.entry(action).or_insert(default);
+        }
+        Self(bindings)
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use Binding::{Gamepad, Key, Mouse};
+        use GamepadButtonType::*;
+
+        Self(HashMap::from([
+            (
+                InputAction::MoveForward,
+                vec![Key(KeyCode::W), Key(KeyCode::Up), Gamepad(DPadUp)],
+            ),
+            (
+                InputAction::MoveBack,
+                vec![Key(KeyCode::S), Key(KeyCode::Down), Gamepad(DPadDown)],
+            ),
+            (
+                InputAction::MoveLeft,
+                vec![Key(KeyCode::A), Key(KeyCode::Left), Gamepad(DPadLeft)],
+            ),
+            (
+                InputAction::MoveRight,
+                vec![Key(KeyCode::D), Key(KeyCode::Right), Gamepad(DPadRight)],
+            ),
+            (
+                InputAction::TurnLeft,
+                vec![Key(KeyCode::Q), Gamepad(LeftTrigger)],
+            ),
+            (
+                InputAction::TurnRight,
+                vec![Key(KeyCode::E), Gamepad(RightTrigger)],
+            ),
+            (InputAction::Jump, vec![Key(KeyCode::Space), Gamepad(South)]),
+            (
+                InputAction::Sprint,
+                vec![Key(KeyCode::ControlLeft), Gamepad(LeftThumb)],
+            ),
+            (
+                InputAction::Dash,
+                vec![Key(KeyCode::ShiftLeft), Gamepad(East)],
+            ),
+            (
+                InputAction::GroundPound,
+                vec![Key(KeyCode::C), Gamepad(West)],
+            ),
+            (InputAction::Grab, vec![Key(KeyCode::F), Gamepad(North)]),
+            (
+                InputAction::Shove,
+                vec![Mouse(MouseButton::Left), Gamepad(RightTrigger2)],
+            ),
+            (
+                InputAction::Fire,
+                vec![Mouse(MouseButton::Right), Gamepad(LeftTrigger2)],
+            ),
+            (InputAction::Restart, vec![Key(KeyCode::R)]),
+            (
+                InputAction::ToggleCamera,
+                vec![Key(KeyCode::V), Gamepad(RightThumb)],
+            ),
+            (
+                InputAction::OpenMenu,
+                vec![Key(KeyCode::Escape), Gamepad(Start)],
+            ),
+            (InputAction::Chat, vec![Key(KeyCode::T)]),
+            (
+                InputAction::Scoreboard,
+                vec![Key(KeyCode::Tab), Gamepad(Select)],
+            ),
+        ]))
+    }
+}
+
+impl InputMap {
+    pub fn bindings(&self, action: InputAction) -> &[Binding] {
+        self.0
+            .get(&action)
+            .map_or(&[][..], |bindings| bindings.as_slice())
+    }
+
+    pub fn pressed(&self, action: InputAction, input: &InputSources) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.pressed(input))
+    }
+
+    pub fn just_pressed(&self, action: InputAction, input: &InputSources) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.just_pressed(input))
+    }
+
+    /// Sets the binding at `index`, or adds it when `index` is past the end.
+    pub fn bind(&mut self, action: InputAction, index: usize, binding: Binding) {
+        let bindings = self.0.entry(action).or_default();
+        match bindings.get_mut(index) {
+            Some(slot) => *slot = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    pub fn unbind(&mut self, action: InputAction, index: usize) {
+        if let Some(bindings) = self.0.get_mut(&action) {
+            if index < bindings.len() {
+                bindings.remove(index);
+            }
+        }
+    }
+
+    /// Other actions sharing `binding` with `action`.
+    pub fn conflicts(&self, action: InputAction, binding: Binding) -> Vec<InputAction> {
+        InputAction::all()
+            .into_iter()
+            .filter(|other| *other != action && self.bindings(*other).contains(&binding))
+            .collect()
+    }
+}
@@ -1,4 +1,6 @@
 #![allow(clippy::module_inception)]
 
+mod input_map;
 mod settings;
+pub use input_map::*;
 pub use settings::*;
@@ -19,23 +19,69 @@ use bevy::{
 use bevy_kira_audio::{prelude::Volume, AudioInstance, AudioTween};
 use serde::{self, Deserialize, Serialize};
 
-use crate::sound::MenuMusic;
+use crate::character::Appearance;
+use crate::sound::{MenuMusic, ProvinceMusic};
+use crate::ui::NameplateInfo;
+
+use super::InputMap;
 
 #[allow(dead_code)]
 #[derive(Debug, Resource, Default)]
 struct AppliedSettings {
     music_volume: f64,
+    mouse_sensitivity: f32,
+    invert_y: bool,
+    gamepad_dead_zone: f32,
+    gamepad_look_sensitivity: f32,
+    input_map: InputMap,
+    appearance: Appearance,
+    nameplate_info: NameplateInfo,
 }
 
+impl From<&Settings> for AppliedSettings {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            music_volume: settings.music_volume,
+            mouse_sensitivity: settings.mouse_sensitivity,
+            invert_y: settings.invert_y,
+            gamepad_dead_zone: settings.gamepad_dead_zone,
+            gamepad_look_sensitivity: settings.gamepad_look_sensitivity,
+            input_map: settings.input_map.clone(),
+            appearance: settings.appearance.clone(),
+            nameplate_info: settings.nameplate_info,
+        }
+    }
+}
+
+/// Missing fields fall back to defaults, so older settings files still load.
 #[allow(dead_code)]
 #[derive(Deserialize, Serialize, Debug, Resource)]
+#[serde(default)]
 pub struct Settings {
     pub music_volume: f64,
+    pub mouse_sensitivity: f32,
+    pub invert_y: bool,
+    /// Stick tilt ignored around the center, from 0 to 1
+    pub gamepad_dead_zone: f32,
+    pub gamepad_look_sensitivity: f32,
+    pub input_map: InputMap,
+    pub appearance: Appearance,
+    /// Extra line under the username on nameplates
+    pub nameplate_info: NameplateInfo,
 }
 
 impl Default for Settings {
     fn default() -> Self {
-        Self { music_volume: 10. }
+        Self {
+            music_volume: 10.,
+            mouse_sensitivity: 1.,
+            invert_y: false,
+            gamepad_dead_zone: 0.15,
+            gamepad_look_sensitivity: 1.,
+            input_map: InputMap::default(),
+            appearance: Appearance::default(),
+            nameplate_info: NameplateInfo::default(),
+        }
     }
 }
 
@@ -68,6 +114,13 @@ fn exempt_settings(
     for _ in event.read() {
         commands.insert_resource(Settings {
             music_volume: applied_settings.music_volume,
+            mouse_sensitivity: applied_settings.mouse_sensitivity,
+            invert_y: applied_settings.invert_y,
+            gamepad_dead_zone: applied_settings.gamepad_dead_zone,
+            gamepad_look_sensitivity: applied_settings.gamepad_look_sensitivity,
+            input_map: applied_settings.input_map.clone(),
+            appearance: applied_settings.appearance.clone(),
+            nameplate_info: applied_settings.nameplate_info,
         });
     }
 }
@@ -77,6 +130,7 @@ fn apply_settings(
     mut event: EventReader<ApplySettings>,
     settings: Res<Settings>,
     menu_music: ResMut<MenuMusic>,
+    province_music: Res<ProvinceMusic>,
     mut audio_sources: ResMut<Assets<AudioInstance>>,
     settings_path: Res<SettingsPath>,
 ) {
@@ -89,14 +143,23 @@ fn apply_settings(
         } else {
             warn!("Failed to get music source");
         }
+        if let Some(instance) = province_music
+            .instance_handle
+            .as_ref()
+            .and_then(|instance_handle| audio_sources.get_mut(instance_handle))
+        {
+            instance.set_volume(
+                Volume::Amplitude(settings.music_volume / 10.),
+                AudioTween::default(),
+            );
+        }
 
-        commands.insert_resource(AppliedSettings {
-            music_volume: settings.music_volume,
-        });
+        commands.insert_resource(AppliedSettings::from(settings.as_ref()));
 
         let settings_path = settings_path.as_ref().as_ref();
         let mut file = OpenOptions::new()
             .write(true)
+            .truncate(true)
             .open(settings_path)
             .unwrap_or_else(|err| {
                 panic!(
@@ -179,5 +242,6 @@ fn setup(mut commands: Commands) {
         }
     };
 
+    commands.insert_resource(AppliedSettings::from(&settings));
     commands.insert_resource(settings);
 }
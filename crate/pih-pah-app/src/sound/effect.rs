@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+
+use crate::settings::Settings;
+
+const CHECKPOINT_SOUND_PATH: &str = "checkpoint.wav";
+const HIT_SOUND_PATH: &str = "hit.wav";
+
+/// Short sounds of gameplay feedback, loaded once.
+#[derive(Default, Resource)]
+pub struct SoundEffects {
+    pub checkpoint: Handle<AudioSource>,
+    pub hit: Handle<AudioSource>,
+}
+
+impl SoundEffects {
+    /// Plays a sound once at the music volume, the only volume of the settings.
+    pub fn play(&self, audio: &Audio, settings: &Settings, source: &Handle<AudioSource>) {
+        audio
+            .play(source.clone())
+            .with_volume(Volume::Amplitude(settings.music_volume / 10.));
+    }
+}
+
+pub struct SoundEffectPlugins;
+
+impl Plugin for SoundEffectPlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundEffects>()
+            .add_systems(Startup, setup);
+    }
+}
+
+fn setup(asset_server: Res<AssetServer>, mut effects: ResMut<SoundEffects>) {
+    effects.checkpoint = asset_server.load(CHECKPOINT_SOUND_PATH);
+    effects.hit = asset_server.load(HIT_SOUND_PATH);
+}
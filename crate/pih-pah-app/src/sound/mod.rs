@@ -5,3 +5,6 @@ pub use sound::*;
 
 mod music;
 pub use music::*;
+
+mod effect;
+pub use effect::*;
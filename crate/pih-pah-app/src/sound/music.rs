@@ -3,7 +3,8 @@ use bevy_kira_audio::prelude::*;
 use rand::{thread_rng, Rng};
 use std::time::Duration;
 
-use crate::settings::ApplySettings;
+use crate::province::{ProvinceState, Provinces};
+use crate::settings::{ApplySettings, Settings};
 
 const MINIMAL_DELAY: f32 = 15.;
 const MAXIMAL_DELAY: f32 = 90.;
@@ -19,14 +20,27 @@ pub struct MenuMusic {
     duration: Option<Duration>,
 }
 
+/// Looped track of the loaded province, the menu music waits while it plays.
+#[derive(Default, Resource)]
+pub struct ProvinceMusic {
+    pub instance_handle: Option<Handle<AudioInstance>>,
+}
+
 pub struct MusicPlugins;
 
 impl Plugin for MusicPlugins {
     fn build(&self, app: &mut App) {
         app.init_resource::<MenuMusic>()
             .init_resource::<MusicTimer>()
+            .init_resource::<ProvinceMusic>()
             .add_systems(Startup, setup)
-            .add_systems(Update, play_music);
+            .add_systems(
+                Update,
+                (
+                    play_music,
+                    play_province_music.run_if(state_changed::<ProvinceState>()),
+                ),
+            );
     }
 }
 
@@ -45,10 +59,14 @@ fn play_music(
     audio: Res<Audio>,
     mut music_timer: ResMut<MusicTimer>,
     mut menu_music: ResMut<MenuMusic>,
+    province_music: Res<ProvinceMusic>,
     audio_sources: Res<Assets<AudioSource>>,
     mut event: EventWriter<ApplySettings>,
 ) {
     if music_timer.tick(time.delta()).just_finished() {
+        if province_music.instance_handle.is_some() {
+            return;
+        }
         if menu_music.duration.is_none() {
             if let Some(audio_source) = audio_sources.get(&menu_music.source_handle) {
                 let duration = audio_source.sound.duration();
@@ -68,3 +86,37 @@ fn play_music(
         event.send(ApplySettings);
     }
 }
+
+fn play_province_music(
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    provinces: Res<Provinces>,
+    province_state: Res<State<ProvinceState>>,
+    menu_music: Res<MenuMusic>,
+    mut province_music: ResMut<ProvinceMusic>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    if let Some(instance_handle) = province_music.instance_handle.take() {
+        if let Some(instance) = audio_instances.get_mut(&instance_handle) {
+            instance.stop(AudioTween::default());
+        }
+    }
+    let Some(music) = provinces
+        .get(province_state.get())
+        .and_then(|manifest| manifest.music.as_ref())
+    else {
+        return;
+    };
+
+    if let Some(instance) = audio_instances.get_mut(&menu_music.instance_handle) {
+        instance.stop(AudioTween::default());
+    }
+    province_music.instance_handle = Some(
+        audio
+            .play(asset_server.load(music.clone()))
+            .looped()
+            .with_volume(Volume::Amplitude(settings.music_volume / 10.))
+            .handle(),
+    );
+}
@@ -0,0 +1,193 @@
+use crate::settings::{Binding, InputAction, InputMap, InputSources, Settings};
+use crate::ui::rich_text;
+use crate::util::i18n::Uniq::Module;
+use bevy::input::gamepad::GamepadButton;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiInput, EguiSet};
+
+lazy_static::lazy_static! {
+    static ref MODULE: &'static str = module_path!().splitn(3, ':').nth(2).unwrap_or(module_path!());
+}
+
+/// Binding slot waiting for the next key, mouse or gamepad button, Escape cancels.
+#[derive(Debug, Default, Resource)]
+pub struct Rebinding(pub Option<(InputAction, usize)>);
+
+pub struct ControlsPlugins;
+
+impl Plugin for ControlsPlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(
+                PreUpdate,
+                gamepad_navigation
+                    .after(EguiSet::ProcessInput)
+                    .before(EguiSet::BeginFrame),
+            )
+            .add_systems(
+                Update,
+                capture_binding.run_if(|rebinding: Res<Rebinding>| rebinding.0.is_some()),
+            );
+    }
+}
+
+fn capture_binding(
+    input: InputSources,
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some((action, index)) = rebinding.0 else {
+        return;
+    };
+    if input.keys.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+    let binding = input
+        .keys
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            input
+                .mouse_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            input
+                .gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Gamepad(button.button_type))
+        });
+    if let Some(binding) = binding {
+        settings.input_map.bind(action, index, binding);
+        rebinding.0 = None;
+    }
+}
+
+/// Lets the d-pad walk egui widgets: up/down move focus, South clicks, East backs out.
+fn gamepad_navigation(
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    rebinding: Res<Rebinding>,
+    mut egui_input_query: Query<&mut EguiInput, With<PrimaryWindow>>,
+) {
+    // the pressed button is being bound, it should not also click around
+    if rebinding.0.is_some() {
+        return;
+    }
+    let Ok(mut egui_input) = egui_input_query.get_single_mut() else {
+        return;
+    };
+    for button in gamepad_buttons.get_just_pressed() {
+        let (key, modifiers) = match button.button_type {
+            GamepadButtonType::DPadDown => (egui::Key::Tab, egui::Modifiers::NONE),
+            GamepadButtonType::DPadUp => (egui::Key::Tab, egui::Modifiers::SHIFT),
+            GamepadButtonType::South => (egui::Key::Enter, egui::Modifiers::NONE),
+            GamepadButtonType::East => (egui::Key::Escape, egui::Modifiers::NONE),
+            _ => continue,
+        };
+        for pressed in [true, false] {
+            egui_input.0.events.push(egui::Event::Key {
+                key,
+                pressed,
+                repeat: false,
+                modifiers,
+            });
+        }
+    }
+}
+
+/// Binding list of the settings windows.
+pub fn controls_ui(
+    ui: &mut egui::Ui,
+    settings: &mut Settings,
+    rebinding: &mut Rebinding,
+    font: &egui::FontId,
+) {
+    egui::CollapsingHeader::new(rich_text("Controls".to_string(), Module(&MODULE), font))
+        .id_source("controls")
+        .show(ui, |ui| {
+            egui::Grid::new("controls_grid").show(ui, |ui| {
+                for action in InputAction::all() {
+                    ui.label(rich_text(action.to_string(), Module(&MODULE), font));
+                    ui.horizontal(|ui| {
+                        let bindings = settings.input_map.bindings(action).to_vec();
+                        for (index, binding) in bindings.iter().enumerate() {
+                            let text = if rebinding.0 == Some((action, index)) {
+                                "...".to_string()
+                            } else {
+                                binding.to_string()
+                            };
+                            let conflicts = settings.input_map.conflicts(action, *binding);
+                            let mut button = egui::Button::new(text);
+                            if !conflicts.is_empty() {
+                                button = button.fill(egui::Color32::DARK_RED);
+                            }
+                            let response = ui.add(button);
+                            let response = if conflicts.is_empty() {
+                                response
+                            } else {
+                                let actions: Vec<String> =
+                                    conflicts.iter().map(|other| other.to_string()).collect();
+                                response
+                                    .on_hover_text(format!("Also bound to {}", actions.join(", ")))
+                            };
+                            if response.clicked() {
+                                rebinding.0 = Some((action, index));
+                            }
+                            if response.secondary_clicked() {
+                                settings.input_map.unbind(action, index);
+                            }
+                        }
+                        let adding = rebinding.0 == Some((action, bindings.len()));
+                        if ui.button(if adding { "..." } else { "+" }).clicked() {
+                            rebinding.0 = Some((action, bindings.len()));
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(rich_text(
+                    "Stick dead zone".to_string(),
+                    Module(&MODULE),
+                    font,
+                ));
+                ui.add(egui::Slider::new(
+                    &mut settings.gamepad_dead_zone,
+                    0.0..=0.9,
+                ));
+            });
+            ui.horizontal(|ui| {
+                ui.label(rich_text(
+                    "Stick look sensitivity".to_string(),
+                    Module(&MODULE),
+                    font,
+                ));
+                ui.add(egui::Slider::new(
+                    &mut settings.gamepad_look_sensitivity,
+                    0.1..=5.0,
+                ));
+            });
+            ui.label(rich_text(
+                "Click to rebind, right click to remove".to_string(),
+                Module(&MODULE),
+                font,
+            ));
+            if ui
+                .button(rich_text(
+                    "Reset to defaults".to_string(),
+                    Module(&MODULE),
+                    font,
+                ))
+                .clicked()
+            {
+                settings.input_map = InputMap::default();
+                rebinding.0 = None;
+            }
+        });
+}
@@ -0,0 +1,46 @@
+use crate::character::{Appearance, CharacterModels, Hat};
+use crate::ui::rich_text;
+use crate::util::i18n::Uniq::Module;
+use bevy_egui::egui;
+
+lazy_static::lazy_static! {
+    static ref MODULE: &'static str = module_path!().splitn(3, ':').nth(2).unwrap_or(module_path!());
+}
+
+/// Model and hat pickers of the customization window.
+pub fn customization_ui(
+    ui: &mut egui::Ui,
+    appearance: &mut Appearance,
+    models: &CharacterModels,
+    font: &egui::FontId,
+) {
+    let model_text = |model: &Option<String>| model.clone().unwrap_or_else(|| "Cube".to_string());
+
+    ui.horizontal(|ui| {
+        ui.label(rich_text("Model: ".to_string(), Module(&MODULE), font));
+        egui::ComboBox::from_id_source("customization_model")
+            .selected_text(model_text(&appearance.model))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut appearance.model, None, model_text(&None));
+                for model in models.0.iter() {
+                    ui.selectable_value(&mut appearance.model, Some(model.clone()), model);
+                }
+            });
+    });
+    if let Some(model) = &appearance.model {
+        if !models.contains(model) {
+            ui.label(rich_text(
+                format!("{} is missing, the cube is used", model),
+                Module(&MODULE),
+                font,
+            ));
+        }
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(rich_text("Hat: ".to_string(), Module(&MODULE), font));
+        for hat in Hat::all() {
+            ui.selectable_value(&mut appearance.hat, hat, hat.to_string());
+        }
+    });
+}
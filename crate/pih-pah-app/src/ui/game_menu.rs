@@ -1,10 +1,14 @@
-use crate::lobby::host::ChangeProvinceServerEvent;
+use crate::character::{CharacterConfig, JumpConfig};
+use crate::game_mode::GameModeState;
+use crate::lobby::host::{ChangeGameModeServerEvent, ChangeProvinceServerEvent};
+use crate::lobby::room::RoomState;
 use crate::lobby::LobbyState;
-use crate::province::ProvinceState;
+use crate::province::{ProvinceState, Provinces};
 use crate::settings::{ApplySettings, ExemptSettings, Settings};
-use crate::ui::{rich_text, UiAction, TRANSPARENT};
+use crate::ui::{controls_ui, rich_text, Rebinding, UiAction, TRANSPARENT};
 use crate::util::i18n::Uniq::Module;
 use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
 use bevy_egui::egui::Align2;
 use bevy_egui::{egui, EguiContexts};
 
@@ -22,14 +26,18 @@ struct EguiState {
     is_active: bool,
     selected_map: ProvinceState,
     selected_map_applied: ProvinceState,
+    selected_game_mode: GameModeState,
+    selected_game_mode_applied: GameModeState,
 }
 
 impl Default for EguiState {
     fn default() -> Self {
         Self {
             is_active: false,
-            selected_map: ProvinceState::ShootingRange,
-            selected_map_applied: ProvinceState::ShootingRange,
+            selected_map: ProvinceState::Menu,
+            selected_map_applied: ProvinceState::Menu,
+            selected_game_mode: GameModeState::default(),
+            selected_game_mode_applied: GameModeState::default(),
         }
     }
 }
@@ -39,6 +47,7 @@ enum WindowState {
     #[default]
     None,
     Settings,
+    Movement,
 }
 
 pub struct GameMenuPlugins;
@@ -57,6 +66,19 @@ impl Plugin for GameMenuPlugins {
                 settings_window
                     .run_if(in_state(UiState::GameMenu).and_then(in_state(WindowState::Settings))),
             )
+            .add_systems(
+                Update,
+                movement_window.run_if(
+                    in_state(UiState::GameMenu)
+                        .and_then(in_state(WindowState::Movement))
+                        .and_then(not(in_state(LobbyState::Client))),
+                ),
+            )
+            .add_systems(Update, grab_cursor)
+            .add_systems(
+                Update,
+                sync_selected_map.run_if(state_changed::<ProvinceState>()),
+            )
             .add_systems(OnExit(WindowState::Settings), exempt_setting);
     }
 }
@@ -85,6 +107,7 @@ fn menu(
     mut context: EguiContexts,
     mut state: ResMut<EguiState>,
     mut ui_game_menu_writer: EventWriter<GameMenuEvent>,
+    lobby_state: Res<State<LobbyState>>,
 ) {
     let ctx = context.ctx_mut();
 
@@ -114,6 +137,14 @@ fn menu(
                 {
                     next_state_menu_window.set(WindowState::Settings);
                 }
+                // movement is tuned by whoever simulates the characters
+                if *lobby_state.get() != LobbyState::Client
+                    && ui
+                        .button(rich_text("Movement".to_string(), Module(&MODULE), &font))
+                        .clicked()
+                {
+                    next_state_menu_window.set(WindowState::Movement);
+                }
                 if ui
                     .button(rich_text("Menu".to_string(), Module(&MODULE), &font))
                     .clicked()
@@ -132,13 +163,18 @@ fn menu(
 fn settings_window(
     mut next_state_menu_window: ResMut<NextState<WindowState>>,
     mut next_state_province: ResMut<NextState<ProvinceState>>,
+    mut next_state_game_mode: ResMut<NextState<GameModeState>>,
     mut context: EguiContexts,
     mut windows: Query<&Window>,
     mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
     mut state: ResMut<EguiState>,
     lobby_state: Res<State<LobbyState>>,
+    room_state: Res<State<RoomState>>,
     mut settings_applying: EventWriter<ApplySettings>,
     mut change_province: EventWriter<ChangeProvinceServerEvent>,
+    mut change_game_mode: EventWriter<ChangeGameModeServerEvent>,
+    provinces: Res<Provinces>,
 ) {
     let window = windows.single_mut();
     let window_size = egui::vec2(window.width(), window.height());
@@ -171,7 +207,23 @@ fn settings_window(
                 ));
                 ui.add(egui::Slider::new(&mut settings.music_volume, 0.0..=200.0).text("%"));
             });
-            if *lobby_state.get() != LobbyState::Client {
+            ui.label(rich_text("Mouse: ".to_string(), Module(&MODULE), &font));
+            ui.horizontal(|ui| {
+                ui.label(rich_text("Sensitivity".to_string(), Module(&MODULE), &font));
+                ui.add(egui::Slider::new(
+                    &mut settings.mouse_sensitivity,
+                    0.1..=5.0,
+                ));
+            });
+            ui.checkbox(
+                &mut settings.invert_y,
+                rich_text("Invert Y".to_string(), Module(&MODULE), &font),
+            );
+            controls_ui(ui, &mut settings, &mut rebinding, &font);
+            // in a multiplayer room the province is picked in the room window
+            if *lobby_state.get() != LobbyState::Client
+                && matches!(room_state.get(), RoomState::None | RoomState::Playing)
+            {
                 ui.label(rich_text("Province: ".to_string(), Module(&MODULE), &font));
                 ui.horizontal(|ui| {
                     egui::ComboBox::from_label(rich_text(
@@ -179,18 +231,34 @@ fn settings_window(
                         Module(&MODULE),
                         &font,
                     ))
-                    .selected_text(format!("{}", state.selected_map))
+                    .selected_text(provinces.name(&state.selected_map))
                     .show_ui(ui, |ui| {
-                        ui.selectable_value(
-                            &mut state.selected_map,
-                            ProvinceState::ShootingRange,
-                            ProvinceState::ShootingRange.to_string(),
-                        );
-                        ui.selectable_value(
-                            &mut state.selected_map,
-                            ProvinceState::GravityHell,
-                            ProvinceState::GravityHell.to_string(),
-                        );
+                        for province_state in provinces.states() {
+                            let name = provinces.name(&province_state);
+                            ui.selectable_value(&mut state.selected_map, province_state, name);
+                        }
+                    });
+                });
+                let game_modes = provinces.game_modes(&state.selected_map);
+                if !game_modes.contains(&state.selected_game_mode) {
+                    state.selected_game_mode = game_modes[0];
+                }
+                ui.label(rich_text("Game mode: ".to_string(), Module(&MODULE), &font));
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label(rich_text(
+                        "Game mode".to_string(),
+                        Module(&MODULE),
+                        &font,
+                    ))
+                    .selected_text(format!("{}", state.selected_game_mode))
+                    .show_ui(ui, |ui| {
+                        for game_mode in game_modes {
+                            ui.selectable_value(
+                                &mut state.selected_game_mode,
+                                game_mode,
+                                game_mode.to_string(),
+                            );
+                        }
                     });
                 });
             }
@@ -206,9 +274,14 @@ fn settings_window(
                     .clicked()
                 {
                     if state.selected_map_applied != state.selected_map {
-                        state.selected_map_applied = state.selected_map;
-                        next_state_province.set(state.selected_map);
-                        change_province.send(ChangeProvinceServerEvent(state.selected_map));
+                        state.selected_map_applied = state.selected_map.clone();
+                        next_state_province.set(state.selected_map.clone());
+                        change_province.send(ChangeProvinceServerEvent(state.selected_map.clone()));
+                    }
+                    if state.selected_game_mode_applied != state.selected_game_mode {
+                        state.selected_game_mode_applied = state.selected_game_mode;
+                        next_state_game_mode.set(state.selected_game_mode);
+                        change_game_mode.send(ChangeGameModeServerEvent(state.selected_game_mode));
                     }
                     settings_applying.send(ApplySettings);
                 }
@@ -217,9 +290,14 @@ fn settings_window(
                     .clicked()
                 {
                     if state.selected_map_applied != state.selected_map {
-                        state.selected_map_applied = state.selected_map;
-                        next_state_province.set(state.selected_map);
-                        change_province.send(ChangeProvinceServerEvent(state.selected_map));
+                        state.selected_map_applied = state.selected_map.clone();
+                        next_state_province.set(state.selected_map.clone());
+                        change_province.send(ChangeProvinceServerEvent(state.selected_map.clone()));
+                    }
+                    if state.selected_game_mode_applied != state.selected_game_mode {
+                        state.selected_game_mode_applied = state.selected_game_mode;
+                        next_state_game_mode.set(state.selected_game_mode);
+                        change_game_mode.send(ChangeGameModeServerEvent(state.selected_game_mode));
                     }
                     settings_applying.send(ApplySettings);
                     next_state_menu_window.set(WindowState::None);
@@ -228,7 +306,145 @@ fn settings_window(
         });
 }
 
-fn exempt_setting(mut event: EventWriter<ExemptSettings>, mut state: ResMut<EguiState>) {
-    state.selected_map = state.selected_map_applied;
+/// Cursor is locked for mouse look while playing and released by any open menu.
+fn grab_cursor(
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    state: Res<EguiState>,
+    ui_state: Res<State<UiState>>,
+    room_state: Res<State<RoomState>>,
+    menu_window_state: Res<State<WindowState>>,
+) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    let grab = *ui_state.get() == UiState::GameMenu
+        && !state.is_active
+        && *menu_window_state.get() == WindowState::None
+        && matches!(room_state.get(), RoomState::None | RoomState::Playing);
+    let grab_mode = if grab {
+        CursorGrabMode::Locked
+    } else {
+        CursorGrabMode::None
+    };
+    if window.cursor.grab_mode != grab_mode {
+        window.cursor.grab_mode = grab_mode;
+        window.cursor.visible = !grab;
+    }
+}
+
+fn movement_window(
+    mut next_state_menu_window: ResMut<NextState<WindowState>>,
+    mut context: EguiContexts,
+    mut character_config: ResMut<CharacterConfig>,
+    mut jump_config: ResMut<JumpConfig>,
+    provinces: Res<Provinces>,
+    province_state: Res<State<ProvinceState>>,
+) {
+    let ctx = context.ctx_mut();
+
+    let font = egui::FontId {
+        family: egui::FontFamily::Monospace,
+        ..default()
+    };
+
+    // edit a copy so unchanged frames are not replicated
+    let mut config = character_config.clone();
+    let mut jump = jump_config.clone();
+
+    egui::Window::new(rich_text("Movement".to_string(), Module(&MODULE), &font))
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .collapsible(false)
+        .resizable(false)
+        .movable(false)
+        .show(ctx, |ui| {
+            let sliders = [
+                ("Max ground speed", &mut config.max_ground_speed, 0.0..=50.0),
+                ("Max air speed", &mut config.max_air_speed, 0.0..=50.0),
+                (
+                    "Sprint multiplier",
+                    &mut config.sprint_multiplier,
+                    1.0..=3.0,
+                ),
+                (
+                    "Ground acceleration",
+                    &mut config.ground_acceleration,
+                    0.0..=200.0,
+                ),
+                (
+                    "Air acceleration",
+                    &mut config.air_acceleration,
+                    0.0..=200.0,
+                ),
+                (
+                    "Acceleration curve",
+                    &mut config.acceleration_curve,
+                    0.0..=4.0,
+                ),
+                ("Ground friction", &mut config.ground_friction, 0.0..=200.0),
+                ("Air friction", &mut config.air_friction, 0.0..=50.0),
+                ("Turn speed", &mut config.turn_speed, 0.0..=5.0),
+                (
+                    "Max ground angle",
+                    &mut jump.max_ground_angle_degrees,
+                    0.0..=90.0,
+                ),
+                ("Jump height", &mut jump.height, 0.0..=10.0),
+                ("Coyote time", &mut jump.coyote_time, 0.0..=0.5),
+                ("Jump buffer", &mut jump.buffer_time, 0.0..=0.5),
+                ("Jump release", &mut jump.release_factor, 0.0..=1.0),
+                ("Wall jump push", &mut jump.wall_jump_push, 0.0..=30.0),
+            ];
+            for (label, value, range) in sliders {
+                ui.horizontal(|ui| {
+                    ui.label(rich_text(label.to_string(), Module(&MODULE), &font));
+                    ui.add(egui::Slider::new(value, range));
+                });
+            }
+            ui.checkbox(
+                &mut jump.wall_jump,
+                rich_text("Wall jump".to_string(), Module(&MODULE), &font),
+            );
+            ui.horizontal(|ui| {
+                if ui
+                    .button(rich_text("Reset".to_string(), Module(&MODULE), &font))
+                    .clicked()
+                {
+                    config = CharacterConfig::default();
+                    // jumping is set per province by its manifest
+                    jump = provinces
+                        .get(province_state.get())
+                        .map_or_else(JumpConfig::default, |manifest| manifest.jump.clone());
+                }
+                if ui
+                    .button(rich_text("Ok".to_string(), Module(&MODULE), &font))
+                    .clicked()
+                {
+                    next_state_menu_window.set(WindowState::None);
+                }
+            });
+        });
+
+    if config != *character_config {
+        *character_config = config;
+    }
+    if jump != *jump_config {
+        *jump_config = jump;
+    }
+}
+
+fn exempt_setting(
+    mut event: EventWriter<ExemptSettings>,
+    mut state: ResMut<EguiState>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    rebinding.0 = None;
+    state.selected_map = state.selected_map_applied.clone();
+    state.selected_game_mode = state.selected_game_mode_applied;
     event.send(ExemptSettings);
 }
+
+/// Keeps the selector on the loaded province, which the host or a room may change.
+fn sync_selected_map(mut state: ResMut<EguiState>, province_state: Res<State<ProvinceState>>) {
+    state.selected_map = province_state.get().clone();
+    state.selected_map_applied = province_state.get().clone();
+}
@@ -0,0 +1,271 @@
+use std::collections::HashSet;
+
+use crate::character::{Ability, AbilityCooldowns, Damage, MyShots, ShotStats, WeaponConfig};
+use crate::game_mode::{GameModeState, Round, RoundPhase, RoundTimer, Winner};
+use crate::lobby::{Lobby, PlayerId};
+use crate::settings::{InputAction, Settings};
+use crate::ui::{color32, rich_text, TRANSPARENT};
+use crate::util::i18n::Uniq::Module;
+use crate::world::{Checkpoint, Me, MyCheckpoint};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::UiState;
+
+lazy_static::lazy_static! {
+    static ref MODULE: &'static str = module_path!().splitn(3, ':').nth(2).unwrap_or(module_path!());
+}
+
+pub struct HudPlugins;
+
+impl Plugin for HudPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            round_hud.run_if(in_state(UiState::GameMenu).and_then(not(in_state(RoundPhase::None)))),
+        )
+        .add_systems(
+            Update,
+            (ability_hud, checkpoint_hud).run_if(in_state(UiState::GameMenu)),
+        )
+        .add_systems(
+            Update,
+            weapon_hud
+                .run_if(in_state(UiState::GameMenu).and_then(resource_exists::<WeaponConfig>())),
+        );
+    }
+}
+
+fn player_name(lobby: Option<&Lobby>, player_id: PlayerId) -> String {
+    lobby
+        .and_then(|lobby| lobby.players.get(&player_id))
+        .map(|player_data| player_data.username.clone())
+        .unwrap_or_else(|| format!("{:?}", player_id))
+}
+
+fn round_hud(
+    mut context: EguiContexts,
+    lobby: Option<Res<Lobby>>,
+    round: Res<Round>,
+    round_timer: Res<RoundTimer>,
+    round_phase: Res<State<RoundPhase>>,
+    game_mode: Res<State<GameModeState>>,
+) {
+    let ctx = context.ctx_mut();
+
+    let font = egui::FontId {
+        family: egui::FontFamily::Monospace,
+        ..default()
+    };
+
+    let lobby = lobby.as_deref();
+
+    egui::Window::new(rich_text("Round".to_string(), Module(&MODULE), &font))
+        .frame(*TRANSPARENT)
+        .anchor(egui::Align2::CENTER_TOP, [0., 10.])
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .movable(false)
+        .show(ctx, |ui| {
+            let mut header = format!("{} | {}", game_mode.get(), round_phase.get());
+            if round.number > 0 {
+                header += &format!(" | Round {}", round.number);
+            }
+            if let Some(remaining) = round_timer.remaining_secs() {
+                let remaining = remaining.ceil() as u32;
+                header += &format!(" | {:02}:{:02}", remaining / 60, remaining % 60);
+            }
+            ui.label(rich_text(header, Module(&MODULE), &font));
+
+            if *round_phase.get() == RoundPhase::RoundEnd {
+                let winner = match round.winner {
+                    Some(Winner::Player(player_id)) => {
+                        format!("{} wins", player_name(lobby, player_id))
+                    }
+                    Some(Winner::Team(team)) => format!("{} team wins", team),
+                    None => "Draw".to_string(),
+                };
+                ui.label(rich_text(winner, Module(&MODULE), &font));
+            }
+
+            // players with lives left are listed even before scoring
+            let mut scores: Vec<_> = round
+                .scores
+                .keys()
+                .chain(round.lives.keys())
+                .copied()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .map(|player_id| (player_id, round.score(player_id)))
+                .collect();
+            scores.sort_by(|(_, a), (_, b)| b.cmp(a));
+            for (player_id, score) in scores {
+                let color = lobby
+                    .and_then(|lobby| lobby.players.get(&player_id))
+                    .map_or(Color::WHITE, |player_data| player_data.character_color());
+                let mut line = format!("{} {}", player_name(lobby, player_id), score);
+                if round.eliminated.contains(&player_id) {
+                    line += " (out)";
+                } else if let Some(lives) = round.lives.get(&player_id) {
+                    line += &format!(" lives {}", lives);
+                }
+                ui.colored_label(color32(color), line);
+            }
+        });
+}
+
+fn ability_hud(
+    mut context: EguiContexts,
+    cooldowns_query: Query<(&AbilityCooldowns, &Damage), With<Me>>,
+) {
+    let Ok((cooldowns, damage)) = cooldowns_query.get_single() else {
+        return;
+    };
+    let ctx = context.ctx_mut();
+
+    let font = egui::FontId {
+        family: egui::FontFamily::Monospace,
+        ..default()
+    };
+
+    egui::Window::new(rich_text("Abilities".to_string(), Module(&MODULE), &font))
+        .frame(*TRANSPARENT)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0., -10.])
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .movable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(rich_text(
+                    format!("{:.0}%", damage.0),
+                    Module(&MODULE),
+                    &font,
+                ));
+                for ability in Ability::all() {
+                    let remaining = cooldowns.remaining(ability);
+                    if remaining > 0. {
+                        ui.colored_label(
+                            egui::Color32::GRAY,
+                            format!("{} {:.1}", ability, remaining),
+                        );
+                    } else {
+                        ui.label(rich_text(ability.to_string(), Module(&MODULE), &font));
+                    }
+                }
+            });
+        });
+}
+
+fn checkpoint_hud(
+    mut context: EguiContexts,
+    settings: Res<Settings>,
+    my_checkpoint: Res<MyCheckpoint>,
+    checkpoint_query: Query<&Checkpoint>,
+) {
+    if checkpoint_query.is_empty() {
+        return;
+    }
+    let ctx = context.ctx_mut();
+
+    let font = egui::FontId {
+        family: egui::FontFamily::Monospace,
+        ..default()
+    };
+
+    let total = checkpoint_query.iter().count();
+    let reached = checkpoint_query
+        .iter()
+        .filter(|Checkpoint(order)| my_checkpoint.progress.reached(*order))
+        .count();
+    let restart = settings
+        .input_map
+        .bindings(InputAction::Restart)
+        .first()
+        .map_or_else(String::new, |binding| format!(" | {} to restart", binding));
+
+    egui::Window::new(rich_text("Checkpoints".to_string(), Module(&MODULE), &font))
+        .frame(*TRANSPARENT)
+        .anchor(egui::Align2::LEFT_TOP, [10., 10.])
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .movable(false)
+        .show(ctx, |ui| {
+            ui.label(rich_text(
+                format!("Checkpoint {}/{}{}", reached, total, restart),
+                Module(&MODULE),
+                &font,
+            ));
+            if !my_checkpoint.flash.finished() {
+                ui.colored_label(egui::Color32::GREEN, "Checkpoint reached");
+            }
+        });
+}
+
+fn weapon_hud(
+    mut context: EguiContexts,
+    lobby: Option<Res<Lobby>>,
+    my_shots: Res<MyShots>,
+    stats_query: Query<&ShotStats>,
+) {
+    let ctx = context.ctx_mut();
+
+    let font = egui::FontId {
+        family: egui::FontFamily::Monospace,
+        ..default()
+    };
+
+    egui::Area::new("Crosshair")
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .interactable(false)
+        .show(ctx, |ui| {
+            if my_shots.marker.finished() {
+                ui.label(rich_text("+".to_string(), Module(&MODULE), &font));
+            } else {
+                ui.colored_label(egui::Color32::RED, "X");
+            }
+        });
+
+    // without a lobby only the local player shoots
+    let mut rows: Vec<(String, Color, ShotStats)> = match lobby.as_deref() {
+        Some(lobby) => lobby
+            .players
+            .iter()
+            .filter_map(|(player_id, player_data)| {
+                let stats = stats_query.get(player_data.entity?).ok()?;
+                Some((
+                    player_name(Some(lobby), *player_id),
+                    player_data.character_color(),
+                    *stats,
+                ))
+            })
+            .collect(),
+        None => vec![("You".to_string(), Color::WHITE, my_shots.stats)],
+    };
+    rows.sort_by(|(_, _, a), (_, _, b)| b.points.cmp(&a.points));
+
+    egui::Window::new(rich_text("Shots".to_string(), Module(&MODULE), &font))
+        .frame(*TRANSPARENT)
+        .anchor(egui::Align2::RIGHT_TOP, [-10., 10.])
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .movable(false)
+        .show(ctx, |ui| {
+            for (name, color, stats) in rows {
+                let accuracy = stats.accuracy().map_or_else(
+                    || "-".to_string(),
+                    |accuracy| format!("{:.0}%", accuracy * 100.),
+                );
+                ui.colored_label(
+                    color32(color),
+                    format!(
+                        "{} {} pts {}/{} {}",
+                        name, stats.points, stats.hits, stats.shots, accuracy
+                    ),
+                );
+            }
+        });
+}
@@ -1,8 +1,9 @@
+use crate::character::CharacterModels;
 use crate::load::LoadEvent;
 use crate::lobby::{ClientResource, HostResource, LobbyState};
-use crate::province::ProvinceState;
+use crate::province::{ProvinceState, Provinces};
 use crate::settings::{ApplySettings, ExemptSettings, Settings};
-use crate::ui::{rich_text, TRANSPARENT};
+use crate::ui::{controls_ui, customization_ui, rich_text, NameplateInfo, Rebinding, TRANSPARENT};
 use crate::util::i18n::Uniq::Module;
 use bevy::app::AppExit;
 use bevy::prelude::*;
@@ -35,6 +36,7 @@ enum WindowState {
     None,
     Multiplayer,
     Settings,
+    Character,
 }
 
 impl Default for State {
@@ -61,6 +63,12 @@ impl Plugin for MenuPlugins {
                     .run_if(in_state(UiState::Menu).and_then(in_state(WindowState::Settings))),
             )
             .add_systems(OnExit(WindowState::Settings), exempt_setting)
+            .add_systems(
+                Update,
+                character_window
+                    .run_if(in_state(UiState::Menu).and_then(in_state(WindowState::Character))),
+            )
+            .add_systems(OnExit(WindowState::Character), exempt_setting)
             .add_systems(
                 Update,
                 multiplayer_window
@@ -76,6 +84,7 @@ fn menu(
     mut next_state_menu_window: ResMut<NextState<WindowState>>,
     mut context: EguiContexts,
     mut exit: EventWriter<AppExit>,
+    provinces: Res<Provinces>,
 ) {
     let ctx = context.ctx_mut();
 
@@ -91,16 +100,19 @@ fn menu(
         .resizable(false)
         .movable(false)
         .show(ctx, |ui| {
-            if ui
-                .button(rich_text(
-                    "Shooting range".to_string(),
-                    Module(&MODULE),
-                    &font,
-                ))
-                .clicked()
+            // single player starts in the default province, if any manifest was found
+            let province_state = provinces.default_state();
+            if province_state != ProvinceState::Menu
+                && ui
+                    .button(rich_text(
+                        provinces.name(&province_state),
+                        Module(&MODULE),
+                        &font,
+                    ))
+                    .clicked()
             {
                 next_state_ui.set(UiState::GameMenu);
-                next_state_province.set(ProvinceState::ShootingRange);
+                next_state_province.set(province_state);
                 event_load.send(LoadEvent(LobbyState::Single));
             }
             if ui
@@ -109,6 +121,12 @@ fn menu(
             {
                 next_state_menu_window.set(WindowState::Multiplayer);
             }
+            if ui
+                .button(rich_text("Character".to_string(), Module(&MODULE), &font))
+                .clicked()
+            {
+                next_state_menu_window.set(WindowState::Character);
+            }
             if ui
                 .button(rich_text("Settings".to_string(), Module(&MODULE), &font))
                 .clicked()
@@ -126,10 +144,8 @@ fn menu(
 
 #[allow(clippy::too_many_arguments)]
 fn multiplayer_window(
-    mut event_load: EventWriter<LoadEvent>,
     mut next_state_ui: ResMut<NextState<UiState>>,
     mut next_state_lobby: ResMut<NextState<LobbyState>>,
-    mut next_state_province: ResMut<NextState<ProvinceState>>,
     mut next_state_menu_window: ResMut<NextState<WindowState>>,
     mut context: EguiContexts,
     mut state: ResMut<State>,
@@ -186,8 +202,8 @@ fn multiplayer_window(
                             Some(format!("127.0.0.1:{}", state.host_port.clone()));
                         host_resource.username = Some(state.username.clone());
                         next_state_menu_window.set(WindowState::None);
-                        event_load.send(LoadEvent(LobbyState::Host));
-                        next_state_province.set(ProvinceState::ShootingRange);
+                        // province is loaded by the room once the host starts the match
+                        next_state_lobby.set(LobbyState::Host);
                         next_state_ui.set(UiState::GameMenu);
                     }
                 }
@@ -236,6 +252,7 @@ fn settings_window(
     mut context: EguiContexts,
     mut windows: Query<&Window>,
     mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
     mut settings_applying: EventWriter<ApplySettings>,
 ) {
     let window = windows.single_mut();
@@ -264,6 +281,32 @@ fn settings_window(
                 ui.label(format!("Music: {}", settings.music_volume));
                 ui.add(egui::Slider::new(&mut settings.music_volume, 0.0..=200.0).text("%"));
             });
+            ui.horizontal(|ui| {
+                ui.label(rich_text(
+                    "Mouse sensitivity".to_string(),
+                    Module(&MODULE),
+                    &font,
+                ));
+                ui.add(egui::Slider::new(
+                    &mut settings.mouse_sensitivity,
+                    0.1..=5.0,
+                ));
+            });
+            ui.checkbox(
+                &mut settings.invert_y,
+                rich_text("Invert Y".to_string(), Module(&MODULE), &font),
+            );
+            ui.horizontal(|ui| {
+                ui.label(rich_text(
+                    "Nameplate info: ".to_string(),
+                    Module(&MODULE),
+                    &font,
+                ));
+                for info in NameplateInfo::all() {
+                    ui.selectable_value(&mut settings.nameplate_info, info, info.to_string());
+                }
+            });
+            controls_ui(ui, &mut settings, &mut rebinding, &font);
             ui.horizontal(|ui| {
                 if ui
                     .button(rich_text("Cansel".to_string(), Module(&MODULE), &font))
@@ -288,6 +331,46 @@ fn settings_window(
         });
 }
 
-fn exempt_setting(mut event: EventWriter<ExemptSettings>) {
+fn character_window(
+    mut next_state_menu_window: ResMut<NextState<WindowState>>,
+    mut context: EguiContexts,
+    mut settings: ResMut<Settings>,
+    models: Res<CharacterModels>,
+    mut settings_applying: EventWriter<ApplySettings>,
+) {
+    let ctx = context.ctx_mut();
+
+    let font = egui::FontId {
+        family: egui::FontFamily::Monospace,
+        ..default()
+    };
+
+    egui::Window::new(rich_text("Character".to_string(), Module(&MODULE), &font))
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .collapsible(false)
+        .resizable(false)
+        .movable(false)
+        .show(ctx, |ui| {
+            customization_ui(ui, &mut settings.appearance, &models, &font);
+            ui.horizontal(|ui| {
+                if ui
+                    .button(rich_text("Cansel".to_string(), Module(&MODULE), &font))
+                    .clicked()
+                {
+                    next_state_menu_window.set(WindowState::None);
+                }
+                if ui
+                    .button(rich_text("Ok".to_string(), Module(&MODULE), &font))
+                    .clicked()
+                {
+                    settings_applying.send(ApplySettings);
+                    next_state_menu_window.set(WindowState::None);
+                }
+            });
+        });
+}
+
+fn exempt_setting(mut event: EventWriter<ExemptSettings>, mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
     event.send(ExemptSettings);
 }
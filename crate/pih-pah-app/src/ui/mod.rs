@@ -1,11 +1,21 @@
 #![allow(clippy::module_inception)]
 
+mod controls;
+mod customization;
 mod egui_frame_preset;
 mod game_menu;
+mod hud;
 mod menu;
+mod nameplate;
+mod room;
 mod ui;
 
+pub use controls::*;
+pub use customization::*;
 use egui_frame_preset::*;
 pub use game_menu::*;
+pub use hud::*;
 pub use menu::*;
+pub use nameplate::*;
+pub use room::*;
 pub use ui::*;
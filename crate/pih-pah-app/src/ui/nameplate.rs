@@ -0,0 +1,150 @@
+use std::fmt::Display;
+
+use crate::character::PLAYER_SIZE;
+use crate::lobby::{Lobby, Ping};
+use crate::settings::Settings;
+use crate::ui::color32;
+use crate::world::{Me, MyLayers};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_xpbd_3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::UiState;
+
+/// Height of the plate above the character center
+const NAMEPLATE_HEIGHT: f32 = PLAYER_SIZE * 1.2;
+/// Plates are fully opaque up to this distance and fade out until `NAMEPLATE_FADE_END`
+const NAMEPLATE_FADE_START: f32 = 15.;
+const NAMEPLATE_FADE_END: f32 = 40.;
+
+/// Extra line shown under the username on nameplates.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum NameplateInfo {
+    #[default]
+    None,
+    Team,
+    Ping,
+}
+
+impl Display for NameplateInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameplateInfo::None => write!(f, "None"),
+            NameplateInfo::Team => write!(f, "Team"),
+            NameplateInfo::Ping => write!(f, "Ping"),
+        }
+    }
+}
+
+impl NameplateInfo {
+    pub fn all() -> [NameplateInfo; 3] {
+        [
+            NameplateInfo::None,
+            NameplateInfo::Team,
+            NameplateInfo::Ping,
+        ]
+    }
+}
+
+pub struct NameplatePlugins;
+
+impl Plugin for NameplatePlugins {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, nameplates.run_if(in_state(UiState::GameMenu)));
+    }
+}
+
+/// Draws the username of every other player above its character, behind the windows.
+fn nameplates(
+    mut context: EguiContexts,
+    settings: Res<Settings>,
+    lobby: Option<Res<Lobby>>,
+    spatial_query: SpatialQuery,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    character_query: Query<(&GlobalTransform, Option<&Ping>), Without<Me>>,
+    me_query: Query<Entity, With<Me>>,
+) {
+    let Some(lobby) = lobby else {
+        return;
+    };
+    let Some((camera, camera_transform)) = camera_query.iter().find(|(camera, _)| camera.is_active)
+    else {
+        return;
+    };
+    let ctx = context.ctx_mut();
+    let painter = ctx.layer_painter(egui::LayerId::background());
+
+    let font = egui::FontId {
+        family: egui::FontFamily::Monospace,
+        ..default()
+    };
+    let small_font = egui::FontId {
+        size: font.size * 0.8,
+        ..font.clone()
+    };
+
+    let eye = camera_transform.translation();
+    for player_data in lobby.players.values() {
+        let Some(entity) = player_data.entity else {
+            continue;
+        };
+        let Ok((transform, ping)) = character_query.get(entity) else {
+            continue;
+        };
+
+        let head = transform.translation() + Vec3::Y * NAMEPLATE_HEIGHT;
+        let offset = head - eye;
+        let distance = offset.length();
+        if distance >= NAMEPLATE_FADE_END {
+            continue;
+        }
+        let Some(direction) = offset.try_normalize() else {
+            continue;
+        };
+        // behind a wall, the plate would give the player away, sensor volumes are see-through
+        let filter = SpatialQueryFilter::new()
+            .with_masks([MyLayers::Default, MyLayers::ActorNoclip])
+            .without_entities(me_query.iter().chain(std::iter::once(entity)));
+        if spatial_query
+            .cast_ray(eye, direction, distance, true, filter)
+            .is_some()
+        {
+            continue;
+        }
+        let Some(position) = camera.world_to_viewport(camera_transform, head) else {
+            continue;
+        };
+
+        let alpha = 1.
+            - ((distance - NAMEPLATE_FADE_START) / (NAMEPLATE_FADE_END - NAMEPLATE_FADE_START))
+                .clamp(0., 1.);
+        let position = egui::pos2(position.x, position.y);
+        painter.text(
+            position,
+            egui::Align2::CENTER_BOTTOM,
+            &player_data.username,
+            font.clone(),
+            color32(player_data.character_color()).gamma_multiply(alpha),
+        );
+
+        let info = match settings.nameplate_info {
+            NameplateInfo::None => None,
+            NameplateInfo::Team => player_data
+                .team
+                .map(|team| (team.to_string(), color32(team.color()))),
+            NameplateInfo::Ping => {
+                ping.map(|ping| (format!("{} ms", ping.0), egui::Color32::LIGHT_GRAY))
+            }
+        };
+        if let Some((text, color)) = info {
+            painter.text(
+                position,
+                egui::Align2::CENTER_TOP,
+                text,
+                small_font.clone(),
+                color.gamma_multiply(alpha),
+            );
+        }
+    }
+}
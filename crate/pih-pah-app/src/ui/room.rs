@@ -0,0 +1,217 @@
+use crate::lobby::room::{room_palette, Room, RoomAction, RoomState};
+use crate::lobby::team::{TeamId, MAX_TEAMS};
+use crate::lobby::{Lobby, LobbyState, PlayerId};
+use crate::province::Provinces;
+use crate::ui::{color32, rich_text};
+use crate::util::i18n::Uniq::Module;
+use bevy::prelude::*;
+use bevy_egui::egui::Align2;
+use bevy_egui::{egui, EguiContexts};
+
+lazy_static::lazy_static! {
+    static ref MODULE: &'static str = module_path!().splitn(3, ':').nth(2).unwrap_or(module_path!());
+}
+
+pub struct RoomMenuPlugins;
+
+impl Plugin for RoomMenuPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            room_window.run_if(
+                in_state(RoomState::Waiting)
+                    .or_else(in_state(RoomState::Countdown))
+                    .and_then(resource_exists::<Room>())
+                    .and_then(resource_exists::<Lobby>()),
+            ),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn room_window(
+    mut context: EguiContexts,
+    mut windows: Query<&Window>,
+    mut room_action: EventWriter<RoomAction>,
+    lobby: Res<Lobby>,
+    room: Res<Room>,
+    lobby_state: Res<State<LobbyState>>,
+    room_state: Res<State<RoomState>>,
+    provinces: Res<Provinces>,
+) {
+    let window = windows.single_mut();
+    let window_size = egui::vec2(window.width(), window.height());
+
+    let ctx = context.ctx_mut();
+
+    let font = egui::FontId {
+        family: egui::FontFamily::Monospace,
+        ..default()
+    };
+
+    let egui_window_size = egui::vec2(400.0, 200.0);
+
+    let center_position = egui::pos2(window_size.x / 2.0, window_size.y / 2.0);
+
+    let is_host = *lobby_state.get() == LobbyState::Host;
+    let me = room.me.and_then(|me| lobby.players.get(&me));
+
+    egui::Window::new(rich_text("Room".to_string(), Module(&MODULE), &font))
+        .pivot(Align2::CENTER_CENTER)
+        .fixed_size(egui_window_size)
+        .fixed_pos(center_position)
+        .collapsible(false)
+        .resizable(false)
+        .movable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(rich_text("Province: ".to_string(), Module(&MODULE), &font));
+                if is_host {
+                    let mut selected_map = room.province_state.clone();
+                    egui::ComboBox::from_id_source("room_province")
+                        .selected_text(provinces.name(&selected_map))
+                        .show_ui(ui, |ui| {
+                            for province_state in provinces.states() {
+                                let name = provinces.name(&province_state);
+                                ui.selectable_value(&mut selected_map, province_state, name);
+                            }
+                        });
+                    if selected_map != room.province_state {
+                        room_action.send(RoomAction::Province(selected_map));
+                    }
+                } else {
+                    ui.label(provinces.name(&room.province_state));
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(rich_text("Game mode: ".to_string(), Module(&MODULE), &font));
+                if is_host {
+                    let mut selected_game_mode = room.game_mode;
+                    egui::ComboBox::from_id_source("room_game_mode")
+                        .selected_text(format!("{}", selected_game_mode))
+                        .show_ui(ui, |ui| {
+                            for game_mode in provinces.game_modes(&room.province_state) {
+                                ui.selectable_value(
+                                    &mut selected_game_mode,
+                                    game_mode,
+                                    game_mode.to_string(),
+                                );
+                            }
+                        });
+                    if selected_game_mode != room.game_mode {
+                        room_action.send(RoomAction::GameMode(selected_game_mode));
+                    }
+                } else {
+                    ui.label(room.game_mode.to_string());
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(rich_text("Teams: ".to_string(), Module(&MODULE), &font));
+                let team_count_text = |team_count: u8| match team_count {
+                    0 => "None".to_string(),
+                    count => count.to_string(),
+                };
+                if is_host {
+                    let mut team_count = room.team_count;
+                    egui::ComboBox::from_id_source("room_team_count")
+                        .selected_text(team_count_text(team_count))
+                        .show_ui(ui, |ui| {
+                            for count in 0..=MAX_TEAMS {
+                                ui.selectable_value(&mut team_count, count, team_count_text(count));
+                            }
+                        });
+                    if team_count != room.team_count {
+                        room_action.send(RoomAction::TeamCount(team_count));
+                    }
+                } else {
+                    ui.label(team_count_text(room.team_count));
+                }
+            });
+
+            ui.separator();
+            let mut players: Vec<_> = lobby.players.iter().collect();
+            players.sort_by(|(_, a), (_, b)| a.username.cmp(&b.username));
+            for (player_id, player_data) in players {
+                ui.horizontal(|ui| {
+                    ui.colored_label(color32(player_data.color), "■");
+                    ui.label(&player_data.username);
+                    if let Some(team) = player_data.team {
+                        ui.colored_label(color32(team.color()), team.to_string());
+                    }
+                    if *player_id == PlayerId::Host {
+                        ui.label(rich_text("(host)".to_string(), Module(&MODULE), &font));
+                    }
+                    let ready = if player_data.ready {
+                        "Ready"
+                    } else {
+                        "Not ready"
+                    };
+                    ui.label(rich_text(ready.to_string(), Module(&MODULE), &font));
+                });
+            }
+            ui.separator();
+
+            if let Some(me) = me {
+                ui.horizontal(|ui| {
+                    ui.label(rich_text("Color: ".to_string(), Module(&MODULE), &font));
+                    for color in room_palette() {
+                        let button = egui::Button::new("  ")
+                            .fill(color32(color))
+                            .selected(color == me.color);
+                        if ui.add(button).clicked() {
+                            room_action.send(RoomAction::Color(color));
+                        }
+                    }
+                });
+
+                if room.team_count > 0 {
+                    ui.horizontal(|ui| {
+                        ui.label(rich_text("Team: ".to_string(), Module(&MODULE), &font));
+                        for team in TeamId::all(room.team_count) {
+                            let button = egui::Button::new(team.to_string())
+                                .fill(color32(team.color()))
+                                .selected(Some(team) == me.team);
+                            if ui.add(button).clicked() {
+                                room_action.send(RoomAction::Team(team));
+                            }
+                        }
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    let text = if me.ready { "Not ready" } else { "Ready" };
+                    if ui
+                        .button(rich_text(text.to_string(), Module(&MODULE), &font))
+                        .clicked()
+                    {
+                        room_action.send(RoomAction::Ready(!me.ready));
+                    }
+                    if is_host
+                        && ui
+                            .add_enabled(
+                                lobby.is_everyone_ready()
+                                    && *room_state.get() == RoomState::Waiting,
+                                egui::Button::new(rich_text(
+                                    "Start".to_string(),
+                                    Module(&MODULE),
+                                    &font,
+                                )),
+                            )
+                            .clicked()
+                    {
+                        room_action.send(RoomAction::Start);
+                    }
+                });
+            }
+
+            if *room_state.get() == RoomState::Countdown {
+                ui.label(rich_text(
+                    format!("Starting in {:.0}", room.countdown.remaining_secs().ceil()),
+                    Module(&MODULE),
+                    &font,
+                ));
+            }
+        });
+}
@@ -1,5 +1,5 @@
 use crate::ui::menu::MenuPlugins;
-use crate::ui::GameMenuPlugins;
+use crate::ui::{ControlsPlugins, GameMenuPlugins, HudPlugins, NameplatePlugins, RoomMenuPlugins};
 use crate::util::i18n::{trans, Uniq};
 use bevy::prelude::*;
 use bevy_egui::egui::FontId;
@@ -27,7 +27,14 @@ pub struct UiPlugins;
 impl Plugin for UiPlugins {
     fn build(&self, app: &mut App) {
         app.add_state::<UiState>()
-            .add_plugins((MenuPlugins, GameMenuPlugins))
+            .add_plugins((
+                MenuPlugins,
+                GameMenuPlugins,
+                RoomMenuPlugins,
+                HudPlugins,
+                ControlsPlugins,
+                NameplatePlugins,
+            ))
             .add_systems(Startup, (setup, set_egui_debug));
     }
 }
@@ -46,4 +53,9 @@ pub fn rich_text(text: impl Into<Arc<String>>, uniq: Uniq, font: &FontId) -> egu
     egui::RichText::new(trans(text.into(), uniq)).font(font.clone())
 }
 
+pub fn color32(color: Color) -> egui::Color32 {
+    let [r, g, b, a] = color.as_rgba_u8();
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
 fn setup() {}
@@ -1,3 +1,18 @@
+use std::path::PathBuf;
+
+use bevy::asset::io::file::FileAssetReader;
+
+/// Folder the `AssetServer` reads from, relative to its base path.
+pub const ASSET_FOLDER: &str = "asset";
+
+/// `folder` inside the asset folder, resolved like the asset reader does
+/// instead of from the working directory.
+pub fn asset_folder(folder: &str) -> PathBuf {
+    FileAssetReader::get_base_path()
+        .join(ASSET_FOLDER)
+        .join(folder)
+}
+
 pub enum ResourceAction {
     Load,
     Unload,
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::component::{DespawnReason, Respawn};
+use crate::lobby::{ButtonEdge, LobbyState, PlayerInput};
+use crate::province::ProvinceState;
+use crate::settings::Settings;
+use crate::sound::SoundEffects;
+
+use super::{
+    sensor_from_mesh, Me, SceneTagAppExt, SceneTagContext, SceneTagErrorKind, TriggerAction,
+    TriggerVolume,
+};
+
+/// Seconds the "checkpoint" message stays on screen.
+pub const CHECKPOINT_FLASH: f32 = 2.;
+
+/// Checkpoint tagged `cp:<order>`, taking a lower one than already reached does nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct Checkpoint(pub u32);
+
+/// Highest checkpoint order a character reached in the loaded province.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component, Serialize, Deserialize)]
+pub struct CheckpointProgress(pub Option<u32>);
+
+impl CheckpointProgress {
+    /// Moves to `order` unless it would go backwards.
+    pub fn advance(&mut self, order: u32) -> bool {
+        if self.0.is_some_and(|reached| reached >= order) {
+            return false;
+        }
+        self.0 = Some(order);
+        true
+    }
+
+    pub fn reached(&self, order: u32) -> bool {
+        self.0.is_some_and(|reached| reached >= order)
+    }
+}
+
+/// Restart button of a character, the host turns it into presses.
+#[derive(Debug, Default, Component)]
+pub struct RestartButton(ButtonEdge);
+
+/// Checkpoint progress of the local player, followed for feedback.
+#[derive(Debug, Resource)]
+pub struct MyCheckpoint {
+    pub progress: CheckpointProgress,
+    /// Runs while the "checkpoint" message is shown
+    pub flash: Timer,
+}
+
+impl Default for MyCheckpoint {
+    fn default() -> Self {
+        let mut flash = Timer::from_seconds(CHECKPOINT_FLASH, TimerMode::Once);
+        flash.tick(flash.duration());
+        Self {
+            progress: CheckpointProgress::default(),
+            flash,
+        }
+    }
+}
+
+/// Look of the reached checkpoints.
+#[derive(Debug, Default, Resource)]
+struct ReachedMaterial(Handle<StandardMaterial>);
+
+/// Material of a reached checkpoint from the scene, put back on a reset.
+#[derive(Debug, Component)]
+struct IdleMaterial(Handle<StandardMaterial>);
+
+pub struct CheckpointPlugins;
+
+impl Plugin for CheckpointPlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MyCheckpoint>()
+            .init_resource::<ReachedMaterial>()
+            .register_shared_scene_tag("cp", checkpoint_tag)
+            .register_scene_tag("cp", checkpoint_volume_tag)
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    reset_progress.run_if(state_changed::<ProvinceState>()),
+                    restart,
+                )
+                    .run_if(not(in_state(LobbyState::Client))),
+            )
+            .add_systems(Update, (follow_my_checkpoint, paint_checkpoints).chain());
+    }
+}
+
+fn setup(mut materials: ResMut<Assets<StandardMaterial>>, mut reached: ResMut<ReachedMaterial>) {
+    // see-through like the checkpoint volumes of the scenes
+    reached.0 = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.3, 0.9, 0.4, 0.35),
+        emissive: Color::rgb(0.1, 0.6, 0.2),
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+}
+
+/// `cp:<order>`, counted from 1 along the course.
+fn checkpoint_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    let value = tag.text()?;
+    let order = value.parse::<u32>().map_err(|_| tag.invalid(value))?;
+    commands.entity(tag.entity).insert(Checkpoint(order));
+    Ok(())
+}
+
+/// The volume of `cp`, the order is checked by `checkpoint_tag`.
+fn checkpoint_volume_tag(
+    commands: &mut Commands,
+    tag: &SceneTagContext,
+) -> Result<(), SceneTagErrorKind> {
+    commands.entity(tag.entity).insert((
+        TriggerVolume(TriggerAction::Checkpoint),
+        sensor_from_mesh(tag)?,
+    ));
+    Ok(())
+}
+
+/// Progress of one province is not carried to the next.
+fn reset_progress(mut character_query: Query<(&mut Respawn, &mut CheckpointProgress)>) {
+    for (mut respawn, mut progress) in character_query.iter_mut() {
+        respawn.reset_spawn_point();
+        *progress = CheckpointProgress::default();
+    }
+}
+
+/// Sends a character back to the start of a course, only where checkpoints exist.
+fn restart(
+    checkpoint_query: Query<(), With<Checkpoint>>,
+    mut character_query: Query<(
+        &PlayerInput,
+        &mut RestartButton,
+        &mut Respawn,
+        &mut CheckpointProgress,
+    )>,
+) {
+    let course = !checkpoint_query.is_empty();
+    for (input, mut button, mut respawn, mut progress) in character_query.iter_mut() {
+        if !button.0.pressed(input.restart) || !course {
+            continue;
+        }
+        respawn.reset_spawn_point();
+        respawn.insert_reason(DespawnReason::Forced);
+        *progress = CheckpointProgress::default();
+    }
+}
+
+fn follow_my_checkpoint(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+    effects: Res<SoundEffects>,
+    mut my_checkpoint: ResMut<MyCheckpoint>,
+    progress_query: Query<&CheckpointProgress, With<Me>>,
+) {
+    my_checkpoint.flash.tick(time.delta());
+    let progress = progress_query.get_single().copied().unwrap_or_default();
+    if progress == my_checkpoint.progress {
+        return;
+    }
+    if let Some(order) = progress.0 {
+        if !my_checkpoint.progress.reached(order) {
+            effects.play(&audio, &settings, &effects.checkpoint);
+            my_checkpoint.flash.reset();
+        }
+    }
+    my_checkpoint.progress = progress;
+}
+
+/// Reached checkpoints light up for the local player, on a reset they go back to the scene look.
+fn paint_checkpoints(
+    mut commands: Commands,
+    my_checkpoint: Res<MyCheckpoint>,
+    reached_material: Res<ReachedMaterial>,
+    checkpoint_query: Query<(
+        Entity,
+        &Checkpoint,
+        &Handle<StandardMaterial>,
+        Option<&IdleMaterial>,
+    )>,
+) {
+    for (entity, Checkpoint(order), material, idle) in checkpoint_query.iter() {
+        match (my_checkpoint.progress.reached(*order), idle) {
+            (true, None) => {
+                commands
+                    .entity(entity)
+                    .insert((IdleMaterial(material.clone()), reached_material.0.clone()));
+            }
+            (false, Some(IdleMaterial(idle))) => {
+                commands
+                    .entity(entity)
+                    .insert(idle.clone())
+                    .remove::<IdleMaterial>();
+            }
+            _ => {}
+        }
+    }
+}
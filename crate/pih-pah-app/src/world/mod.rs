@@ -1,4 +1,14 @@
 #![allow(clippy::module_inception)]
 
+mod checkpoint;
+mod mover;
+mod scene_tag;
+mod target;
+mod trigger;
 mod world;
+pub use checkpoint::*;
+pub use mover::*;
+pub use scene_tag::*;
+pub use target::*;
+pub use trigger::*;
 pub use world::*;
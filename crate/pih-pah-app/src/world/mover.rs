@@ -0,0 +1,369 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::Collisions;
+use serde::{Deserialize, Serialize};
+
+use crate::character::CharacterController;
+use crate::lobby::LobbyState;
+use crate::province::ProvinceState;
+
+use super::{LinkId, SceneTagAppExt, SceneTagContext, SceneTagErrorKind};
+
+/// Seconds an open door or lift waits without characters before it closes
+pub const MOVER_HOLD: f32 = 2.;
+/// Client clock error snapped at once, smaller ones are eased out
+const MOVER_CLOCK_SNAP: f32 = 0.25;
+const MOVER_CLOCK_EASE: f32 = 0.1;
+/// Time step of the velocity carrying characters
+const MOVER_VELOCITY_STEP: f32 = 0.01;
+
+/// Seconds since the province was loaded, movers are a function of it.
+///
+/// Ticked by the host on the fixed update and replicated, clients run it on and ease it towards the host.
+#[derive(Debug, Default, Clone, Copy, Resource)]
+pub struct MoverClock(pub f32);
+
+impl MoverClock {
+    pub fn sync(&mut self, host: f32) {
+        let error = host - self.0;
+        if error.abs() > MOVER_CLOCK_SNAP {
+            self.0 = host;
+        } else {
+            self.0 += error * MOVER_CLOCK_EASE;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activation {
+    /// Any character touching the node
+    Touch,
+    /// A character standing on the node
+    Stand,
+    /// Only `toggle` triggers
+    Remote,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Motion {
+    /// Back and forth through offsets from the initial position, starting at it
+    PingPong { points: Vec<Vec3>, speed: f32 },
+    /// Around an axis of the node, in rad/s
+    Spin { axis: Vec3, speed: f32 },
+    /// To an offset in `duration` seconds while activated, see `MoverTrigger`
+    Triggered {
+        offset: Vec3,
+        duration: f32,
+        activation: Activation,
+    },
+}
+
+/// Node moved by the host and clients alike from the `MoverClock`, needs a kinematic collider to carry characters.
+#[derive(Debug, Clone, Component)]
+pub struct Mover {
+    pub origin: Transform,
+    pub motion: Motion,
+}
+
+/// Open state of a triggered mover, replicated by `LinkId`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component, Serialize, Deserialize)]
+pub struct MoverTrigger {
+    pub open: bool,
+    /// Progress when `open` last changed
+    pub from: f32,
+    /// Clock time when `open` last changed
+    pub since: f32,
+    /// Clock time of the last activation, only known by the host
+    #[serde(skip)]
+    pub activated: f32,
+    /// Opened by a `toggle` trigger, stays open until toggled again
+    #[serde(skip)]
+    pub latched: bool,
+}
+
+impl MoverTrigger {
+    /// From `0` closed to `1` open.
+    pub fn progress(&self, time: f32, duration: f32) -> f32 {
+        let target = if self.open { 1. } else { 0. };
+        let step = (time - self.since).max(0.) / duration.max(f32::EPSILON);
+        if self.from < target {
+            (self.from + step).min(target)
+        } else {
+            (self.from - step).max(target)
+        }
+    }
+
+    pub fn set_open(&mut self, open: bool, time: f32, duration: f32) {
+        self.from = self.progress(time, duration);
+        self.open = open;
+        self.since = time;
+    }
+
+    /// Opens and latches a closed mover, closes an open one.
+    pub fn toggle(&mut self, time: f32, duration: f32) {
+        let open = !self.open;
+        self.latched = open;
+        self.set_open(open, time, duration);
+    }
+}
+
+impl Mover {
+    pub fn pose(&self, time: f32, trigger: Option<&MoverTrigger>) -> Transform {
+        let mut transform = self.origin;
+        match &self.motion {
+            Motion::PingPong { points, speed } => {
+                transform.translation += ping_pong(points, speed * time);
+            }
+            Motion::Spin { axis, speed } => {
+                transform.rotation *= Quat::from_axis_angle(*axis, speed * time);
+            }
+            Motion::Triggered {
+                offset, duration, ..
+            } => {
+                let progress = trigger.map_or(0., |trigger| trigger.progress(time, *duration));
+                transform.translation += *offset * progress;
+            }
+        }
+        transform
+    }
+
+    /// Velocity of the mover at `point`, what a character standing there is carried with.
+    pub fn velocity_at(&self, time: f32, trigger: Option<&MoverTrigger>, point: Vec3) -> Vec3 {
+        let now = self.pose(time, trigger);
+        let next = self.pose(time + MOVER_VELOCITY_STEP, trigger);
+        let local = now.rotation.inverse() * (point - now.translation);
+        (next.translation + next.rotation * local - point) / MOVER_VELOCITY_STEP
+    }
+}
+
+/// Offset after `distance` meters on the path from zero through `points` and back.
+fn ping_pong(points: &[Vec3], distance: f32) -> Vec3 {
+    let legs: Vec<(Vec3, Vec3)> = std::iter::once(Vec3::ZERO)
+        .chain(points.iter().copied())
+        .collect::<Vec<_>>()
+        .windows(2)
+        .map(|leg| (leg[0], leg[1]))
+        .collect();
+    let length: f32 = legs.iter().map(|(from, to)| from.distance(*to)).sum();
+    if length <= 0. {
+        return Vec3::ZERO;
+    }
+    let mut distance = distance.rem_euclid(length * 2.);
+    if distance > length {
+        distance = length * 2. - distance;
+    }
+    for (from, to) in legs.iter() {
+        let leg = from.distance(*to);
+        if distance <= leg {
+            return from.lerp(*to, distance / leg.max(f32::EPSILON));
+        }
+        distance -= leg;
+    }
+    points.last().copied().unwrap_or_default()
+}
+
+pub struct MoverPlugins;
+
+impl Plugin for MoverPlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MoverClock>()
+            .register_shared_scene_tag("pp", ping_pong_tag)
+            .register_shared_scene_tag("spin", spin_tag)
+            .register_shared_scene_tag("door", door_tag)
+            .register_shared_scene_tag("lift", lift_tag)
+            .register_shared_scene_tag("gate", gate_tag)
+            .add_systems(
+                FixedUpdate,
+                (tick_clock, activate_movers)
+                    .chain()
+                    .run_if(not(in_state(LobbyState::Client))),
+            )
+            .add_systems(Update, tick_clock.run_if(in_state(LobbyState::Client)))
+            .add_systems(
+                Update,
+                (
+                    reset_clock.run_if(state_changed::<ProvinceState>()),
+                    move_movers,
+                    check_mover_links,
+                ),
+            );
+    }
+}
+
+/// `x y z`
+fn parse_vec3(text: &str) -> Option<Vec3> {
+    let values: Vec<f32> = text
+        .split_whitespace()
+        .map(|value| value.parse::<f32>().ok().filter(|value| value.is_finite()))
+        .collect::<Option<_>>()?;
+    match values[..] {
+        [x, y, z] => Some(Vec3::new(x, y, z)),
+        _ => None,
+    }
+}
+
+/// `pp:<m/s>,<x y z>[,<x y z>...]`, back and forth through offsets from the initial position.
+fn ping_pong_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    let value = tag.text()?;
+    let mut params = value.split(',');
+    let speed = params
+        .next()
+        .and_then(|speed| speed.trim().parse::<f32>().ok())
+        .filter(|speed| speed.is_finite() && *speed > 0.)
+        .ok_or_else(|| tag.invalid(value))?;
+    let points: Vec<Vec3> = params
+        .map(parse_vec3)
+        .collect::<Option<_>>()
+        .filter(|points: &Vec<Vec3>| !points.is_empty())
+        .ok_or_else(|| tag.invalid(value))?;
+    insert_mover(commands, tag, Motion::PingPong { points, speed });
+    Ok(())
+}
+
+/// `spin:<deg/s>[,<x y z>]`, around an axis of the node, its y axis by default.
+fn spin_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    let value = tag.text()?;
+    let (speed, axis) = match value.split_once(',') {
+        Some((speed, axis)) => (speed, parse_vec3(axis)),
+        None => (value, Some(Vec3::Y)),
+    };
+    let speed = speed
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|speed| speed.is_finite())
+        .ok_or_else(|| tag.invalid(value))?;
+    let axis = axis
+        .and_then(|axis| axis.try_normalize())
+        .ok_or_else(|| tag.invalid(value))?;
+    insert_mover(
+        commands,
+        tag,
+        Motion::Spin {
+            axis,
+            speed: speed.to_radians(),
+        },
+    );
+    Ok(())
+}
+
+/// `door:<seconds>,<x y z>`, opens by the offset while characters touch it.
+fn door_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    triggered_tag(commands, tag, Activation::Touch)
+}
+
+/// `lift:<seconds>,<x y z>`, rises by the offset while characters stand on it.
+fn lift_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    triggered_tag(commands, tag, Activation::Stand)
+}
+
+/// `gate:<seconds>,<x y z>`, moves by the offset when toggled by a trigger.
+fn gate_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    triggered_tag(commands, tag, Activation::Remote)
+}
+
+fn triggered_tag(
+    commands: &mut Commands,
+    tag: &SceneTagContext,
+    activation: Activation,
+) -> Result<(), SceneTagErrorKind> {
+    let value = tag.text()?;
+    let (duration, offset) = value.split_once(',').ok_or_else(|| tag.invalid(value))?;
+    let duration = duration
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|duration| duration.is_finite() && *duration > 0.)
+        .ok_or_else(|| tag.invalid(value))?;
+    let offset = parse_vec3(offset).ok_or_else(|| tag.invalid(value))?;
+    insert_mover(
+        commands,
+        tag,
+        Motion::Triggered {
+            offset,
+            duration,
+            activation,
+        },
+    );
+    commands.entity(tag.entity).insert(MoverTrigger::default());
+    Ok(())
+}
+
+fn insert_mover(commands: &mut Commands, tag: &SceneTagContext, motion: Motion) {
+    commands.entity(tag.entity).insert(Mover {
+        origin: *tag.transform,
+        motion,
+    });
+}
+
+/// Server tick of the movers on the host, clients run it every frame between host samples.
+fn tick_clock(time: Res<Time>, mut clock: ResMut<MoverClock>) {
+    clock.0 += time.delta_seconds();
+}
+
+fn reset_clock(mut clock: ResMut<MoverClock>) {
+    clock.0 = 0.;
+}
+
+/// Opens doors and lifts used by characters, closes them once left alone.
+fn activate_movers(
+    clock: Res<MoverClock>,
+    collisions: Res<Collisions>,
+    mut mover_query: Query<(Entity, &Mover, &mut MoverTrigger)>,
+    character_query: Query<&CharacterController>,
+) {
+    for (entity, mover, mut trigger) in mover_query.iter_mut() {
+        let Motion::Triggered {
+            duration,
+            activation,
+            ..
+        } = mover.motion
+        else {
+            continue;
+        };
+        let active = match activation {
+            Activation::Touch => collisions.collisions_with_entity(entity).any(|contacts| {
+                character_query.contains(contacts.entity1)
+                    || character_query.contains(contacts.entity2)
+            }),
+            Activation::Stand => character_query
+                .iter()
+                .any(|controller| controller.ground_entity == Some(entity)),
+            Activation::Remote => continue,
+        };
+
+        if active {
+            trigger.activated = clock.0;
+            if !trigger.open {
+                trigger.set_open(true, clock.0, duration);
+            }
+        } else if trigger.open
+            && !trigger.latched
+            && trigger.progress(clock.0, duration) >= 1.
+            && clock.0 - trigger.activated > MOVER_HOLD
+        {
+            trigger.set_open(false, clock.0, duration);
+        }
+    }
+}
+
+fn move_movers(
+    clock: Res<MoverClock>,
+    mut mover_query: Query<(&Mover, Option<&MoverTrigger>, &mut Transform)>,
+) {
+    for (mover, trigger, mut transform) in mover_query.iter_mut() {
+        let pose = mover.pose(clock.0, trigger);
+        if *transform != pose {
+            *transform = pose;
+        }
+    }
+}
+
+/// Triggers are replicated by `LinkId`, clients would never see an unlinked door open.
+fn check_mover_links(trigger_query: Query<Option<&Name>, (Added<MoverTrigger>, Without<LinkId>)>) {
+    for name in trigger_query.iter() {
+        warn!(
+            "Triggered mover {} has no `id` tag and is not replicated",
+            name.map_or("without a name", |name| name.as_str())
+        );
+    }
+}
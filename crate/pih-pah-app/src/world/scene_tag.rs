@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use bevy::ecs::entity::Entities;
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+
+/// One tag of a glTF node name like `cube[c:d;m:5;id:x;r]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SceneTag {
+    pub key: String,
+    pub value: Option<String>,
+    /// Column of the key in the node name, from 1
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneTagErrorKind {
+    UnclosedBracket,
+    TrailingCharacters,
+    EmptyKey,
+    DuplicateKey(String),
+    MissingValue(String),
+    UnexpectedValue(String),
+    InvalidValue { key: String, value: String },
+    MissingMesh(String),
+    InvalidMesh(String),
+}
+
+impl Display for SceneTagErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneTagErrorKind::UnclosedBracket => write!(f, "`[` is never closed"),
+            SceneTagErrorKind::TrailingCharacters => {
+                write!(f, "only a `.001` like suffix may follow `]`")
+            }
+            SceneTagErrorKind::EmptyKey => write!(f, "tag without a key"),
+            SceneTagErrorKind::DuplicateKey(key) => write!(f, "`{}` is given twice", key),
+            SceneTagErrorKind::MissingValue(key) => write!(f, "`{}` needs a value", key),
+            SceneTagErrorKind::UnexpectedValue(key) => write!(f, "`{}` takes no value", key),
+            SceneTagErrorKind::InvalidValue { key, value } => {
+                write!(f, "invalid value `{}` for `{}`", value, key)
+            }
+            SceneTagErrorKind::MissingMesh(key) => write!(f, "`{}` needs a mesh", key),
+            SceneTagErrorKind::InvalidMesh(key) => {
+                write!(f, "`{}` cannot be built from the mesh", key)
+            }
+        }
+    }
+}
+
+/// Malformed node name, `column` counts characters from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SceneTagError {
+    pub name: String,
+    pub column: usize,
+    pub kind: SceneTagErrorKind,
+}
+
+impl Display for SceneTagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.name, self.column, self.kind)
+    }
+}
+
+impl std::error::Error for SceneTagError {}
+
+/// Parses the tags between the brackets of a node name, a name without brackets has none.
+pub fn parse_scene_tags(name: &str) -> Result<Vec<SceneTag>, SceneTagError> {
+    let column = |offset: usize| name[..offset].chars().count() + 1;
+    let error = |offset: usize, kind: SceneTagErrorKind| SceneTagError {
+        name: name.to_string(),
+        column: column(offset),
+        kind,
+    };
+
+    let Some(open) = name.find('[') else {
+        return Ok(Vec::new());
+    };
+    let Some(close) = name[open..].find(']').map(|close| open + close) else {
+        return Err(error(open, SceneTagErrorKind::UnclosedBracket));
+    };
+    // blender appends `.001` to duplicated names
+    let suffix = &name[close + 1..];
+    if !suffix.is_empty()
+        && !(suffix.starts_with('.') && suffix[1..].chars().all(|c| c.is_ascii_digit()))
+    {
+        return Err(error(close + 1, SceneTagErrorKind::TrailingCharacters));
+    }
+
+    let mut tags = Vec::new();
+    let mut offset = open + 1;
+    for param in name[open + 1..close].split(';') {
+        let start = offset;
+        offset += param.len() + 1;
+        if param.trim().is_empty() {
+            continue;
+        }
+        let (key, value) = match param.split_once(':') {
+            Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+            None => (param.trim(), None),
+        };
+        if key.is_empty() {
+            return Err(error(start, SceneTagErrorKind::EmptyKey));
+        }
+        if tags.iter().any(|tag: &SceneTag| tag.key == key) {
+            return Err(error(
+                start,
+                SceneTagErrorKind::DuplicateKey(key.to_string()),
+            ));
+        }
+        tags.push(SceneTag {
+            key: key.to_string(),
+            value,
+            column: column(start),
+        });
+    }
+    Ok(tags)
+}
+
+/// What a tag handler knows about the tagged node.
+pub struct SceneTagContext<'a> {
+    pub entity: Entity,
+    pub key: &'a str,
+    pub value: Option<&'a str>,
+    pub mesh: Option<&'a Mesh>,
+    pub transform: &'a Transform,
+}
+
+impl<'a> SceneTagContext<'a> {
+    /// Value of a `key:value` tag.
+    pub fn text(&self) -> Result<&'a str, SceneTagErrorKind> {
+        self.value
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| SceneTagErrorKind::MissingValue(self.key.to_string()))
+    }
+
+    /// Value of a tag like `m:5`.
+    pub fn number(&self) -> Result<f32, SceneTagErrorKind> {
+        let value = self.text()?;
+        value
+            .parse::<f32>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| self.invalid(value))
+    }
+
+    /// Checks that a tag like `r` has no value.
+    pub fn flag(&self) -> Result<(), SceneTagErrorKind> {
+        match self.value {
+            Some(_) => Err(SceneTagErrorKind::UnexpectedValue(self.key.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn mesh(&self) -> Result<&'a Mesh, SceneTagErrorKind> {
+        self.mesh
+            .ok_or_else(|| SceneTagErrorKind::MissingMesh(self.key.to_string()))
+    }
+
+    pub fn invalid_mesh(&self) -> SceneTagErrorKind {
+        SceneTagErrorKind::InvalidMesh(self.key.to_string())
+    }
+
+    pub fn invalid(&self, value: &str) -> SceneTagErrorKind {
+        SceneTagErrorKind::InvalidValue {
+            key: self.key.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// Inserts the components of one tag, the error is reported with the node name.
+pub type SceneTagHandler = fn(&mut Commands, &SceneTagContext) -> Result<(), SceneTagErrorKind>;
+
+#[derive(Debug, Clone, Copy)]
+struct RegisteredSceneTag {
+    handler: SceneTagHandler,
+    /// Runs on clients too, otherwise only where physics is simulated
+    shared: bool,
+}
+
+/// Handlers of the tags in glTF node names, keyed by the tag key.
+#[derive(Debug, Default, Resource)]
+pub struct SceneTagRegistry {
+    tags: HashMap<String, Vec<RegisteredSceneTag>>,
+}
+
+pub trait SceneTagAppExt {
+    /// Adds a handler run by the host, a key may have several handlers.
+    fn register_scene_tag(&mut self, key: &str, handler: SceneTagHandler) -> &mut Self;
+    /// Adds a handler run by the host and the clients, for tags without physics.
+    fn register_shared_scene_tag(&mut self, key: &str, handler: SceneTagHandler) -> &mut Self;
+}
+
+impl SceneTagAppExt for App {
+    fn register_scene_tag(&mut self, key: &str, handler: SceneTagHandler) -> &mut Self {
+        register(self, key, handler, false)
+    }
+
+    fn register_shared_scene_tag(&mut self, key: &str, handler: SceneTagHandler) -> &mut Self {
+        register(self, key, handler, true)
+    }
+}
+
+fn register(app: &mut App, key: &str, handler: SceneTagHandler, shared: bool) -> &mut App {
+    app.world
+        .get_resource_or_insert_with(SceneTagRegistry::default)
+        .tags
+        .entry(key.to_string())
+        .or_default()
+        .push(RegisteredSceneTag { handler, shared });
+    app
+}
+
+impl SceneTagRegistry {
+    /// Parses and applies the tags of every node below `scene`, reporting all problems of the scene at once.
+    /// A node with an invalid tag gets none of its tags.
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply(
+        &self,
+        commands: &mut Commands,
+        entities: &Entities,
+        scene: Entity,
+        host: bool,
+        parent_query: &Query<&Children>,
+        name_query: &Query<&Name>,
+        mesh_handle_query: &Query<&Handle<Mesh>>,
+        transform_query: &Query<&Transform>,
+        meshes: &Assets<Mesh>,
+    ) {
+        let mut errors = 0;
+        for entity in parent_query.iter_descendants(scene) {
+            let Ok(name) = name_query.get(entity) else {
+                continue;
+            };
+            let tags = match parse_scene_tags(name) {
+                Ok(tags) => tags,
+                Err(err) => {
+                    error!("Invalid scene tags: {}", err);
+                    errors += 1;
+                    continue;
+                }
+            };
+            if tags.is_empty() {
+                continue;
+            }
+
+            let mesh = mesh_handle_query
+                .get(entity)
+                .ok()
+                .and_then(|handle| meshes.get(handle));
+            let transform = transform_query.get(entity).copied().unwrap_or_default();
+            // handlers queue their commands aside, run once every tag of the node is valid
+            let mut queue = CommandQueue::default();
+            let mut node_commands = Commands::new_from_entities(&mut queue, entities);
+            let mut node_errors = 0;
+            for tag in tags.iter() {
+                let Some(handlers) = self.tags.get(&tag.key) else {
+                    warn!("Unknown scene tag `{}` in {}", tag.key, name.as_str());
+                    continue;
+                };
+                let context = SceneTagContext {
+                    entity,
+                    key: &tag.key,
+                    value: tag.value.as_deref(),
+                    mesh,
+                    transform: &transform,
+                };
+                for registered in handlers
+                    .iter()
+                    .filter(|registered| host || registered.shared)
+                {
+                    if let Err(kind) = (registered.handler)(&mut node_commands, &context) {
+                        let err = SceneTagError {
+                            name: name.to_string(),
+                            column: tag.column,
+                            kind,
+                        };
+                        error!("Invalid scene tags: {}", err);
+                        node_errors += 1;
+                    }
+                }
+            }
+            if node_errors > 0 {
+                errors += node_errors;
+                continue;
+            }
+            commands.add(move |world: &mut World| queue.apply(world));
+        }
+        if errors > 0 {
+            warn!("Found {} invalid scene tags", errors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(key: &str, value: Option<&str>, column: usize) -> SceneTag {
+        SceneTag {
+            key: key.to_string(),
+            value: value.map(str::to_string),
+            column,
+        }
+    }
+
+    fn error_at(name: &str) -> (usize, SceneTagErrorKind) {
+        let err = parse_scene_tags(name).unwrap_err();
+        assert_eq!(err.name, name);
+        (err.column, err.kind)
+    }
+
+    #[test]
+    fn name_without_brackets_has_no_tags() {
+        assert_eq!(parse_scene_tags("cube"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn tags_keep_their_columns() {
+        assert_eq!(
+            parse_scene_tags("cube[c:d; m: 5 ;;r]"),
+            Ok(vec![
+                tag("c", Some("d"), 6),
+                tag("m", Some("5"), 10),
+                tag("r", None, 18),
+            ])
+        );
+    }
+
+    #[test]
+    fn columns_count_characters() {
+        assert_eq!(parse_scene_tags("куб[r]"), Ok(vec![tag("r", None, 5)]));
+        assert_eq!(error_at("куб[r"), (4, SceneTagErrorKind::UnclosedBracket));
+    }
+
+    #[test]
+    fn missing_closing_bracket() {
+        assert_eq!(
+            error_at("cube[c:d"),
+            (5, SceneTagErrorKind::UnclosedBracket)
+        );
+    }
+
+    #[test]
+    fn blender_suffix_is_allowed() {
+        assert_eq!(
+            parse_scene_tags("cube[c:d].001"),
+            Ok(vec![tag("c", Some("d"), 6)])
+        );
+        assert_eq!(
+            error_at("cube[c:d].x01"),
+            (10, SceneTagErrorKind::TrailingCharacters)
+        );
+        assert_eq!(
+            error_at("cube[c:d]x"),
+            (10, SceneTagErrorKind::TrailingCharacters)
+        );
+    }
+
+    #[test]
+    fn empty_key() {
+        assert_eq!(error_at("cube[c:d; :5]"), (10, SceneTagErrorKind::EmptyKey));
+        assert_eq!(error_at("cube[:5]"), (6, SceneTagErrorKind::EmptyKey));
+    }
+
+    #[test]
+    fn duplicate_key() {
+        assert_eq!(
+            error_at("cube[m:5;c:d;m:6]"),
+            (14, SceneTagErrorKind::DuplicateKey("m".to_string()))
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::lobby::LobbyState;
+
+use super::{LinkId, MyLayers, SceneTagAppExt, SceneTagContext, SceneTagErrorKind};
+
+/// Seconds a hit target stays down
+pub const TARGET_RESET: f32 = 3.;
+/// Share of the fall done per second
+const TARGET_FALL_SPEED: f32 = 6.;
+
+/// Node tagged `tg:<points>` falling over around its origin when shot.
+#[derive(Debug, Clone, Component)]
+pub struct Target {
+    pub points: u32,
+    pub origin: Transform,
+    /// Shown fall from 0 standing to 1 down, eased on every machine
+    fall: f32,
+}
+
+/// Down state of a target, owned by the host and replicated by `LinkId`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component, Serialize, Deserialize)]
+pub struct TargetState {
+    pub down: bool,
+    /// Seconds until it stands up, only known by the host
+    #[serde(skip)]
+    pub reset: f32,
+}
+
+impl TargetState {
+    /// Knocks the target down, `false` when it already is.
+    pub fn hit(&mut self) -> bool {
+        if self.down {
+            return false;
+        }
+        self.down = true;
+        self.reset = TARGET_RESET;
+        true
+    }
+}
+
+pub struct TargetPlugins;
+
+impl Plugin for TargetPlugins {
+    fn build(&self, app: &mut App) {
+        app.register_shared_scene_tag("tg", target_tag)
+            .register_scene_tag("tg", target_collider_tag)
+            .add_systems(
+                Update,
+                reset_targets.run_if(not(in_state(LobbyState::Client))),
+            )
+            .add_systems(Update, (pose_targets, check_target_links));
+    }
+}
+
+/// `tg:<points>`, awarded to the shooter.
+fn target_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    let value = tag.text()?;
+    let points = value.parse::<u32>().map_err(|_| tag.invalid(value))?;
+    commands.entity(tag.entity).insert((
+        Target {
+            points,
+            origin: *tag.transform,
+            fall: 0.,
+        },
+        TargetState::default(),
+    ));
+    Ok(())
+}
+
+/// The collider shots hit, the points are checked by `target_tag`.
+fn target_collider_tag(
+    commands: &mut Commands,
+    tag: &SceneTagContext,
+) -> Result<(), SceneTagErrorKind> {
+    let collider =
+        Collider::convex_hull_from_mesh(tag.mesh()?).ok_or_else(|| tag.invalid_mesh())?;
+    commands.entity(tag.entity).insert((
+        collider,
+        RigidBody::Static,
+        CollisionLayers::new(
+            [MyLayers::Default],
+            [MyLayers::Default, MyLayers::ActorNoclip],
+        ),
+    ));
+    Ok(())
+}
+
+fn reset_targets(time: Res<Time>, mut target_query: Query<&mut TargetState>) {
+    for mut state in target_query.iter_mut() {
+        if !state.down {
+            continue;
+        }
+        state.reset -= time.delta_seconds();
+        if state.reset <= 0. {
+            state.down = false;
+        }
+    }
+}
+
+fn pose_targets(
+    time: Res<Time>,
+    mut target_query: Query<(&mut Target, &TargetState, &mut Transform)>,
+) {
+    for (mut target, state, mut transform) in target_query.iter_mut() {
+        let fall = if state.down { 1. } else { 0. };
+        if target.fall == fall {
+            continue;
+        }
+        let step = TARGET_FALL_SPEED * time.delta_seconds();
+        target.fall += (fall - target.fall).clamp(-step, step);
+        *transform = target.origin
+            * Transform::from_rotation(Quat::from_rotation_x(-FRAC_PI_2 * target.fall));
+    }
+}
+
+/// Targets are replicated by `LinkId`, clients would never see an unlinked target fall.
+fn check_target_links(target_query: Query<Option<&Name>, (Added<Target>, Without<LinkId>)>) {
+    for name in target_query.iter() {
+        warn!(
+            "Target {} has no `id` tag and is not replicated",
+            name.map_or("without a name", |name| name.as_str())
+        );
+    }
+}
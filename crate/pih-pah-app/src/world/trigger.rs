@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::component::{DespawnReason, Respawn};
+use crate::lobby::LobbyState;
+
+use super::{
+    sensor_from_mesh, Checkpoint, CheckpointProgress, LinkId, Motion, Mover, MoverClock,
+    MoverTrigger, SceneTagAppExt, SceneTagContext, SceneTagErrorKind,
+};
+
+/// Speed of a launch pad without one in its tag, in m/s
+pub const DEFAULT_LAUNCH_SPEED: f32 = 20.;
+
+/// What a trigger does to a body entering it, targets are found by `LinkId`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerAction {
+    /// Only sends `TriggerEvent`s
+    Event,
+    /// Throws the body along the up axis of the trigger at the speed
+    Launch(f32),
+    /// Moves the body to the target, at rest
+    Teleport,
+    /// Respawns the body at the target, or at the trigger without one,
+    /// a `Checkpoint` on the trigger keeps the body from going back
+    Checkpoint,
+    /// Respawns the body
+    Kill,
+    /// Opens or closes the target mover
+    Toggle,
+}
+
+/// Sensor volume tagged `t:<action>`, acts on dynamic bodies entering it.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct TriggerVolume(pub TriggerAction);
+
+/// `target:<link id>` of a trigger.
+#[derive(Debug, Clone, Component)]
+pub struct TriggerTarget(pub LinkId);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerPhase {
+    Enter,
+    Exit,
+}
+
+/// A dynamic body entered or left a trigger, only sent where physics is simulated.
+#[derive(Debug, Clone, Event)]
+pub struct TriggerEvent {
+    pub trigger: Entity,
+    /// `id` of the trigger, how provinces name their events
+    pub name: Option<LinkId>,
+    pub entity: Entity,
+    pub phase: TriggerPhase,
+}
+
+pub struct TriggerPlugins;
+
+impl Plugin for TriggerPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TriggerEvent>()
+            .register_scene_tag("t", trigger_tag)
+            .register_scene_tag("target", target_tag)
+            .add_systems(
+                Update,
+                (detect_triggers, run_trigger_actions)
+                    .chain()
+                    .run_if(not(in_state(LobbyState::Client))),
+            );
+    }
+}
+
+/// `t:event`, `t:launch[,<m/s>]`, `t:teleport`, `t:checkpoint`, `t:kill` or `t:toggle`.
+fn trigger_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    let value = tag.text()?;
+    let (action, param) = match value.split_once(',') {
+        Some((action, param)) => (action.trim(), Some(param.trim())),
+        None => (value, None),
+    };
+    let action = match (action, param) {
+        ("event", None) => TriggerAction::Event,
+        ("launch", None) => TriggerAction::Launch(DEFAULT_LAUNCH_SPEED),
+        ("launch", Some(speed)) => TriggerAction::Launch(
+            speed
+                .parse::<f32>()
+                .ok()
+                .filter(|speed| speed.is_finite())
+                .ok_or_else(|| tag.invalid(value))?,
+        ),
+        ("teleport", None) => TriggerAction::Teleport,
+        ("checkpoint", None) => TriggerAction::Checkpoint,
+        ("kill", None) => TriggerAction::Kill,
+        ("toggle", None) => TriggerAction::Toggle,
+        _ => return Err(tag.invalid(value)),
+    };
+    commands
+        .entity(tag.entity)
+        .insert((TriggerVolume(action), sensor_from_mesh(tag)?));
+    Ok(())
+}
+
+/// `target:<link id>`, the node acted on by the trigger.
+fn target_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    commands
+        .entity(tag.entity)
+        .insert(TriggerTarget(LinkId(tag.text()?.to_string())));
+    Ok(())
+}
+
+fn detect_triggers(
+    mut collision_started: EventReader<CollisionStarted>,
+    mut collision_ended: EventReader<CollisionEnded>,
+    mut trigger_event: EventWriter<TriggerEvent>,
+    trigger_query: Query<Option<&LinkId>, With<TriggerVolume>>,
+    body_query: Query<&RigidBody>,
+) {
+    let started = collision_started
+        .read()
+        .map(|CollisionStarted(a, b)| (*a, *b, TriggerPhase::Enter));
+    let ended = collision_ended
+        .read()
+        .map(|CollisionEnded(a, b)| (*a, *b, TriggerPhase::Exit));
+    for (a, b, phase) in started.chain(ended) {
+        for (trigger, entity) in [(a, b), (b, a)] {
+            let Ok(name) = trigger_query.get(trigger) else {
+                continue;
+            };
+            if !body_query.get(entity).is_ok_and(RigidBody::is_dynamic) {
+                continue;
+            }
+            trigger_event.send(TriggerEvent {
+                trigger,
+                name: name.cloned(),
+                entity,
+                phase,
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_trigger_actions(
+    mut trigger_event: EventReader<TriggerEvent>,
+    clock: Res<MoverClock>,
+    trigger_query: Query<(
+        &TriggerVolume,
+        &GlobalTransform,
+        Option<&TriggerTarget>,
+        Option<&Checkpoint>,
+    )>,
+    link_query: Query<(Entity, &LinkId)>,
+    global_transform_query: Query<&GlobalTransform>,
+    mut body_query: Query<(
+        &mut Position,
+        &mut LinearVelocity,
+        Option<&mut Respawn>,
+        Option<&mut CheckpointProgress>,
+    )>,
+    mut mover_query: Query<(&Mover, &mut MoverTrigger)>,
+) {
+    for event in trigger_event.read() {
+        if event.phase != TriggerPhase::Enter {
+            continue;
+        }
+        let Ok((TriggerVolume(action), trigger_transform, target, checkpoint)) =
+            trigger_query.get(event.trigger)
+        else {
+            continue;
+        };
+        let target = target.and_then(|TriggerTarget(link_id)| {
+            let target = link_query
+                .iter()
+                .find(|(_, id)| *id == link_id)
+                .map(|(entity, _)| entity);
+            if target.is_none() {
+                warn!("Trigger target `{}` does not exist", link_id.0);
+            }
+            target
+        });
+        let target_position = target
+            .and_then(|target| global_transform_query.get(target).ok())
+            .map(|transform| transform.translation());
+        let Ok((mut position, mut linear_velocity, respawn, progress)) =
+            body_query.get_mut(event.entity)
+        else {
+            continue;
+        };
+
+        match action {
+            TriggerAction::Event => {}
+            TriggerAction::Launch(speed) => {
+                let up = trigger_transform.up();
+                linear_velocity.0 += up * (speed - linear_velocity.dot(up));
+            }
+            TriggerAction::Teleport => {
+                // the body arrives at rest, not with the speed it fell into the trigger
+                if let Some(target_position) = target_position {
+                    position.0 = target_position;
+                    linear_velocity.0 = Vec3::ZERO;
+                }
+            }
+            TriggerAction::Checkpoint => {
+                let Some(mut respawn) = respawn else {
+                    continue;
+                };
+                if let (Some(Checkpoint(order)), Some(mut progress)) = (checkpoint, progress) {
+                    if !progress.advance(*order) {
+                        continue;
+                    }
+                }
+                respawn.set_spawn_point(target_position.unwrap_or(trigger_transform.translation()));
+            }
+            TriggerAction::Kill => {
+                if let Some(mut respawn) = respawn {
+                    respawn.insert_reason(DespawnReason::Killed);
+                }
+            }
+            TriggerAction::Toggle => {
+                let Some((mover, mut mover_trigger)) =
+                    target.and_then(|target| mover_query.get_mut(target).ok())
+                else {
+                    continue;
+                };
+                if let Motion::Triggered { duration, .. } = mover.motion {
+                    mover_trigger.toggle(clock.0, duration);
+                }
+            }
+        }
+    }
+}
@@ -1,29 +1,44 @@
-use crate::character::CharacterPlugins;
+use crate::character::{CharacterConfig, CharacterPlugins};
 use crate::component::{ComponentPlugins, Respawn};
+use crate::game_mode::GameModePlugins;
 use crate::load::LoadPlugins;
-use crate::lobby::{LobbyPlugins, LobbyState, PlayerInput};
+use crate::lobby::{LobbyPlugins, LobbyState, PlayerInput, VIEW_PITCH_MAX, VIEW_PITCH_MIN};
 use crate::province::ProvincePlugins;
-use crate::settings::SettingsPlugins;
+use crate::settings::{InputAction, InputSources, Settings, SettingsPlugins};
 use crate::sound::SoundPlugins;
 use crate::ui;
-use crate::ui::{UiAction, UiPlugins};
+use crate::ui::{Rebinding, UiAction, UiPlugins};
+use bevy::ecs::entity::Entities;
+use bevy::input::mouse::MouseMotion;
 use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
 use bevy_xpbd_3d::components::{CollisionLayers, Mass};
-use bevy_xpbd_3d::prelude::{Collider, PhysicsLayer, RigidBody};
+use bevy_xpbd_3d::prelude::{Collider, Friction, PhysicsLayer, Restitution, RigidBody, Sensor};
 use serde::{Deserialize, Serialize};
 
+use super::{
+    CheckpointPlugins, MoverPlugins, SceneTagAppExt, SceneTagContext, SceneTagErrorKind,
+    SceneTagRegistry, TargetPlugins, TriggerPlugins,
+};
+
 #[derive(PhysicsLayer)]
 pub enum MyLayers {
     /// Cannot touch each other
     ActorNoclip,
     Default,
+    /// Trigger and zone volumes, seen by bodies but not by rays
+    Sensor,
 }
 
 #[derive(Component)]
 pub struct PromisedScene;
 
+/// Sent once the tags of a `PromisedScene` are applied.
+#[derive(Debug, Event)]
+pub struct SceneProcessed(pub Entity);
+
 #[derive(Component, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct LinkId(String);
+pub struct LinkId(pub String);
 
 pub struct WorldPlugins;
 
@@ -38,192 +53,261 @@ impl Plugin for WorldPlugins {
             LobbyPlugins,
             CharacterPlugins,
             ComponentPlugins,
+            GameModePlugins,
+            MoverPlugins,
+            TriggerPlugins,
+            CheckpointPlugins,
+            TargetPlugins,
         ))
+        .init_resource::<SceneTagRegistry>()
+        .add_event::<SceneProcessed>()
+        .register_scene_tag("c", collider_tag)
+        .register_shared_scene_tag("id", id_tag)
+        .register_scene_tag("f", friction_tag)
+        .register_scene_tag("b", restitution_tag)
+        .register_scene_tag("m", mass_tag)
+        .register_scene_tag("r", respawn_tag)
         .add_systems(Update, input)
-        .add_systems(
-            Update,
-            process_scene.run_if(
-                not(in_state(LobbyState::None)).and_then(not(in_state(LobbyState::Client))),
-            ),
-        )
-        .add_systems(
-            Update,
-            process_scene_simplified.run_if(in_state(LobbyState::Client)),
-        );
+        .add_systems(Update, process_scene);
     }
 }
 
 #[derive(Component)]
 pub struct Me;
 
+/// Mouse look speed at sensitivity `1`.
+pub const MOUSE_RADIANS_PER_PIXEL: f32 = 0.003;
+
+#[allow(clippy::too_many_arguments)]
 fn input(
-    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    input: InputSources,
+    rebinding: Res<Rebinding>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    settings: Res<Settings>,
+    character_config: Res<CharacterConfig>,
+    windows: Query<&Window, With<PrimaryWindow>>,
     mut ui_game_menu_writer: EventWriter<ui::GameMenuEvent>,
     mut player_input_query: Query<&mut PlayerInput, With<Me>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
+    // the cursor is released while a menu is open
+    let grabbed = windows
+        .get_single()
+        .is_ok_and(|window| window.cursor.grab_mode != CursorGrabMode::None);
+    let look: Vec2 = mouse_motion.read().map(|motion| motion.delta).sum();
+
+    let map = &settings.input_map;
+    let pressed = |action| map.pressed(action, &input);
+    let just_pressed = |action| map.just_pressed(action, &input);
+
+    // the key being bound must not act
+    if rebinding.0.is_some() {
+        return;
+    }
+
+    if just_pressed(InputAction::OpenMenu) {
         ui_game_menu_writer.send(ui::GameMenuEvent(UiAction::Toggle));
     }
 
     if let Ok(mut player_input) = player_input_query.get_single_mut() {
-        player_input.left =
-            keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::Left);
-        player_input.right =
-            keyboard_input.pressed(KeyCode::D) || keyboard_input.pressed(KeyCode::Right);
-        player_input.up = keyboard_input.pressed(KeyCode::W) || keyboard_input.pressed(KeyCode::Up);
-        player_input.down =
-            keyboard_input.pressed(KeyCode::S) || keyboard_input.pressed(KeyCode::Down);
-        player_input.turn_left = keyboard_input.pressed(KeyCode::Q);
-        player_input.turn_right = keyboard_input.pressed(KeyCode::E);
-        player_input.jump = keyboard_input.just_pressed(KeyCode::Space);
-        player_input.sprint = keyboard_input.pressed(KeyCode::ControlLeft);
+        player_input.left = pressed(InputAction::MoveLeft);
+        player_input.right = pressed(InputAction::MoveRight);
+        player_input.up = pressed(InputAction::MoveForward);
+        player_input.down = pressed(InputAction::MoveBack);
+        player_input.turn_left = pressed(InputAction::TurnLeft);
+        player_input.turn_right = pressed(InputAction::TurnRight);
+        player_input.jump = pressed(InputAction::Jump);
+        player_input.sprint = pressed(InputAction::Sprint);
+        player_input.dash = pressed(InputAction::Dash);
+        player_input.ground_pound = pressed(InputAction::GroundPound);
+        player_input.grab = pressed(InputAction::Grab);
+        player_input.shove = pressed(InputAction::Shove);
+        player_input.fire = pressed(InputAction::Fire);
+        player_input.restart = pressed(InputAction::Restart);
+        let stick = input.left_stick(settings.gamepad_dead_zone);
+        player_input.move_x = stick.x;
+        player_input.move_y = stick.y;
+
+        let turn = (player_input.turn_right as i8 - player_input.turn_left as i8) as f32;
+        player_input.yaw += character_config.turn_speed * turn * time.delta_seconds();
+
+        let invert = if settings.invert_y { -1. } else { 1. };
+        // the right stick turns at most at the keyboard turn speed times the sensitivity
+        let look_stick = input.right_stick(settings.gamepad_dead_zone)
+            * character_config.turn_speed
+            * settings.gamepad_look_sensitivity
+            * time.delta_seconds();
+        player_input.yaw -= look_stick.x;
+        player_input.pitch =
+            (player_input.pitch + look_stick.y * invert).clamp(VIEW_PITCH_MIN, VIEW_PITCH_MAX);
+        if grabbed {
+            let sensitivity = MOUSE_RADIANS_PER_PIXEL * settings.mouse_sensitivity;
+            player_input.yaw -= look.x * sensitivity;
+            player_input.pitch = (player_input.pitch - look.y * sensitivity * invert)
+                .clamp(VIEW_PITCH_MIN, VIEW_PITCH_MAX);
+        }
     }
 }
 
-fn process_scene_simplified(
+/// Applies the tags of a newly spawned scene, clients only run the shared ones.
+/// Runs out of a lobby too, as loading waits for the spawn points of the scene.
+#[allow(clippy::too_many_arguments)]
+fn process_scene(
     mut commands: Commands,
-    scene_query: Query<(Entity, &Children), With<PromisedScene>>,
+    entities: &Entities,
+    registry: Res<SceneTagRegistry>,
+    lobby_state: Res<State<LobbyState>>,
+    scene_query: Query<Entity, (With<PromisedScene>, With<Children>)>,
     parent_query: Query<&Children>,
     name_query: Query<&Name>,
+    mesh_handle_query: Query<&Handle<Mesh>>,
+    transform_query: Query<&Transform>,
+    meshes: Res<Assets<Mesh>>,
+    mut scene_processed: EventWriter<SceneProcessed>,
 ) {
-    for (entity, children) in scene_query.iter() {
-        for child in children {
-            process_scene_child_simplified(&mut commands, *child, &parent_query, &name_query);
-        }
-        commands.entity(entity).remove::<PromisedScene>();
+    let host = *lobby_state.get() != LobbyState::Client;
+    for scene in scene_query.iter() {
+        registry.apply(
+            &mut commands,
+            entities,
+            scene,
+            host,
+            &parent_query,
+            &name_query,
+            &mesh_handle_query,
+            &transform_query,
+            &meshes,
+        );
+        commands.entity(scene).remove::<PromisedScene>();
+        scene_processed.send(SceneProcessed(scene));
     }
 }
 
-fn process_scene_child_simplified(
-    commands: &mut Commands,
-    entity: Entity,
-    parent_query: &Query<&Children>,
-    name_query: &Query<&Name>,
-) {
-    if let Ok(name) = name_query.get(entity) {
-        if name.find('[').is_some() {
-            let name = name.split('.').next().unwrap_or(name);
-            let params = name
-                .split('[')
-                .nth(1)
-                .unwrap()
-                .split(']')
-                .next()
-                .unwrap()
-                .split(';');
-            for param in params {
-                let mut split = param.split(':');
-                let name = split.next().unwrap();
-                if let Some(val) = split.next() {
-                    if name == "id" {
-                        commands.entity(entity).insert(LinkId(val.to_string()));
-                    }
+/// `c:<body>[,<shape>]`, body `d`ynamic, `s`tatic, `k`inematic or `t`rigger, shape `mesh`, `hull`,
+/// `decomp`, `box`, `ball` or `capsule`, sized to the node mesh.
+/// Bodies default to `mesh`, triggers to `hull`.
+fn collider_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    let value = tag.text()?;
+    let (body, shape) = match value.split_once(',') {
+        Some((body, shape)) => (body.trim(), Some(shape.trim())),
+        None => (value, None),
+    };
+    let (rigid_body, sensor) = match body {
+        "d" => (RigidBody::Dynamic, false),
+        "s" => (RigidBody::Static, false),
+        "k" => (RigidBody::Kinematic, false),
+        "t" => (RigidBody::Static, true),
+        _ => return Err(tag.invalid(value)),
+    };
+    // bodies keep the trimesh of older scenes, `hull` is opt-in,
+    // triggers get a hull as a trimesh only detects its surface
+    let shape = shape.unwrap_or(if sensor { "hull" } else { "mesh" });
+
+    let mesh = tag.mesh()?;
+    let collider = match shape {
+        "mesh" => Collider::trimesh_from_mesh(mesh),
+        "hull" => Collider::convex_hull_from_mesh(mesh),
+        "decomp" => Collider::convex_decomposition_from_mesh(mesh),
+        "box" | "ball" | "capsule" => mesh.compute_aabb().map(|aabb| {
+            let half = Vec3::from(aabb.half_extents);
+            let primitive = match shape {
+                "box" => Collider::cuboid(half.x * 2., half.y * 2., half.z * 2.),
+                "ball" => Collider::ball(half.max_element()),
+                _ => {
+                    let radius = half.x.max(half.z);
+                    Collider::capsule((half.y - radius).max(0.) * 2., radius)
                 }
+            };
+            let center = Vec3::from(aabb.center);
+            if center == Vec3::ZERO {
+                primitive
+            } else {
+                Collider::compound(vec![(center, Quat::IDENTITY, primitive)])
             }
-        }
+        }),
+        _ => return Err(tag.invalid(value)),
     }
-    if let Ok(children) = parent_query.get(entity) {
-        for child in children {
-            process_scene_child_simplified(commands, *child, parent_query, name_query);
-        }
+    .ok_or_else(|| tag.invalid_mesh())?;
+
+    let mut entity = commands.entity(tag.entity);
+    entity.insert((collider, rigid_body));
+    if sensor {
+        entity.insert((Sensor, sensor_layers()));
+    } else {
+        entity.insert(CollisionLayers::new(
+            [MyLayers::Default],
+            [MyLayers::Default, MyLayers::ActorNoclip, MyLayers::Sensor],
+        ));
     }
+    Ok(())
 }
 
-fn process_scene(
-    mut commands: Commands,
-    scene_query: Query<(Entity, &Children), With<PromisedScene>>,
-    parent_query: Query<&Children>,
-    name_query: Query<&Name>,
-    mesh_handle_query: Query<&Handle<Mesh>>,
-    transform_query: Query<&Transform>,
-    mut meshes: ResMut<Assets<Mesh>>,
-) {
-    for (entity, children) in scene_query.iter() {
-        for child in children {
-            process_scene_child(
-                &mut commands,
-                *child,
-                &parent_query,
-                &name_query,
-                &mesh_handle_query,
-                &transform_query,
-                &mut meshes,
-            );
-        }
-        commands.entity(entity).remove::<PromisedScene>();
+/// Solid sensor volume of the node mesh, for zones and triggers.
+pub fn sensor_from_mesh(
+    tag: &SceneTagContext,
+) -> Result<(Collider, Sensor, RigidBody, CollisionLayers), SceneTagErrorKind> {
+    // solid hulls, a trimesh only detects its surface
+    let collider =
+        Collider::convex_decomposition_from_mesh(tag.mesh()?).ok_or_else(|| tag.invalid_mesh())?;
+    Ok((collider, Sensor, RigidBody::Static, sensor_layers()))
+}
+
+/// Layers of a sensor volume, rays filtering on the solid layers pass through it.
+pub fn sensor_layers() -> CollisionLayers {
+    CollisionLayers::new(
+        [MyLayers::Sensor],
+        [MyLayers::Default, MyLayers::ActorNoclip],
+    )
+}
+
+/// `f:<coefficient>`, friction of a collider.
+fn friction_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    let friction = tag.number()?;
+    if friction < 0. {
+        return Err(tag.invalid(tag.text()?));
     }
+    commands.entity(tag.entity).insert(Friction::new(friction));
+    Ok(())
 }
 
-fn process_scene_child(
+/// `b:<coefficient>`, bounciness of a collider from 0 to 1.
+fn restitution_tag(
     commands: &mut Commands,
-    entity: Entity,
-    parent_query: &Query<&Children>,
-    name_query: &Query<&Name>,
-    mesh_handle_query: &Query<&Handle<Mesh>>,
-    transform_query: &Query<&Transform>,
-    meshes: &mut ResMut<Assets<Mesh>>,
-) {
-    if let Ok(name) = name_query.get(entity) {
-        if name.find('[').is_some() {
-            let name = name.split('.').next().unwrap_or(name);
-            let params = name
-                .split('[')
-                .nth(1)
-                .unwrap()
-                .split(']')
-                .next()
-                .unwrap()
-                .split(';');
-            for param in params {
-                let mut split = param.split(':');
-                let name = split.next().unwrap();
-                if let Some(val) = split.next() {
-                    if name == "c" {
-                        let collider_handler = mesh_handle_query.get(entity).unwrap();
-                        if let Some(mesh) = meshes.get(collider_handler) {
-                            let collider = Collider::trimesh_from_mesh(mesh).unwrap();
-                            commands
-                                .entity(entity)
-                                .insert(collider)
-                                .insert(CollisionLayers::new(
-                                    [MyLayers::Default],
-                                    [MyLayers::Default, MyLayers::ActorNoclip],
-                                ));
-
-                            if val == "d" {
-                                let mut commands_entity = commands.entity(entity);
-                                commands_entity.insert(RigidBody::Dynamic);
-                            }
-                            if val == "s" {
-                                let mut commands_entity = commands.entity(entity);
-                                commands_entity.insert(RigidBody::Static);
-                            }
-                        }
-                    } else if name == "id" {
-                        commands.entity(entity).insert(LinkId(val.to_string()));
-                    } else if name == "m" {
-                        commands.entity(entity).insert(Mass(val.parse().unwrap()));
-                    }
-                } else if name == "r" {
-                    let transform = transform_query.get(entity).unwrap();
-                    commands
-                        .entity(entity)
-                        .insert(Respawn::from_vec3(transform.translation));
-                }
-            }
-        }
+    tag: &SceneTagContext,
+) -> Result<(), SceneTagErrorKind> {
+    let restitution = tag.number()?;
+    if !(0. ..=1.).contains(&restitution) {
+        return Err(tag.invalid(tag.text()?));
     }
-    if let Ok(children) = parent_query.get(entity) {
-        for child in children {
-            process_scene_child(
-                commands,
-                *child,
-                parent_query,
-                name_query,
-                mesh_handle_query,
-                transform_query,
-                meshes,
-            );
-        }
+    commands
+        .entity(tag.entity)
+        .insert(Restitution::new(restitution));
+    Ok(())
+}
+
+/// `id:<link id>`, links the node between host and clients.
+fn id_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    commands
+        .entity(tag.entity)
+        .insert(LinkId(tag.text()?.to_string()));
+    Ok(())
+}
+
+/// `m:<kg>`
+fn mass_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    let mass = tag.number()?;
+    if mass <= 0. {
+        return Err(tag.invalid(tag.text()?));
     }
+    commands.entity(tag.entity).insert(Mass(mass));
+    Ok(())
+}
+
+/// `r`, respawns at the initial position.
+fn respawn_tag(commands: &mut Commands, tag: &SceneTagContext) -> Result<(), SceneTagErrorKind> {
+    tag.flag()?;
+    commands
+        .entity(tag.entity)
+        .insert(Respawn::from_vec3(tag.transform.translation));
+    Ok(())
 }